@@ -0,0 +1,79 @@
+use bitcoin_message_finder::{
+    check_transaction_for_messages, check_transaction_with_options, extract_hidden_message,
+    extract_printable_runs, scan_bytes, ExtractMode, ScanOptions, Transaction, MIN_RUN_LENGTH,
+};
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+
+/// A 16-input segwit transaction with ~13 KB of witness data, some of it
+/// hiding text, plus OP_RETURN and bare multisig outputs.
+const LARGE_TX: &str = include_str!("fixtures/large_tx.json");
+
+fn large_tx() -> Transaction {
+    serde_json::from_str(LARGE_TX).expect("valid fixture")
+}
+
+/// The longest witness item of the fixture, as hex.
+fn witness_hex(tx: &Transaction) -> &str {
+    tx.vin
+        .iter()
+        .flat_map(|vin| &vin.txinwitness)
+        .max_by_key(|item| item.len())
+        .expect("fixture has witness data")
+}
+
+/// Bytes of script and witness data scanned per transaction.
+fn field_bytes(tx: &Transaction) -> u64 {
+    let vin: usize = tx
+        .vin
+        .iter()
+        .map(|vin| vin.txinwitness.iter().map(String::len).sum::<usize>())
+        .sum();
+    let vout: usize = tx
+        .vout
+        .iter()
+        .filter_map(|vout| vout.script_pub_key.as_ref()?.hex.as_ref())
+        .map(String::len)
+        .sum();
+    ((vin + vout) / 2) as u64
+}
+
+fn extraction(c: &mut Criterion) {
+    let tx = large_tx();
+    let hex = witness_hex(&tx);
+    let mut group = c.benchmark_group("extract");
+    group.throughput(Throughput::Bytes((hex.len() / 2) as u64));
+    group.bench_function("hidden_message", |b| {
+        b.iter(|| extract_hidden_message(black_box(hex)))
+    });
+    group.bench_function("printable_runs", |b| {
+        b.iter(|| extract_printable_runs(black_box(hex), MIN_RUN_LENGTH))
+    });
+    let mut buffer = vec![0; hex.len() / 2];
+    group.bench_function("scan_bytes", |b| {
+        b.iter(|| {
+            hex::decode_to_slice(black_box(hex), &mut buffer).unwrap();
+            scan_bytes(&buffer, MIN_RUN_LENGTH)
+        })
+    });
+    group.finish();
+}
+
+fn transaction(c: &mut Criterion) {
+    let tx = large_tx();
+    let runs = ScanOptions {
+        mode: ExtractMode::Runs,
+        ..ScanOptions::default()
+    };
+    let mut group = c.benchmark_group("check_transaction");
+    group.throughput(Throughput::Bytes(field_bytes(&tx)));
+    group.bench_function("whole", |b| {
+        b.iter(|| check_transaction_for_messages(black_box(&tx)))
+    });
+    group.bench_function("runs", |b| {
+        b.iter(|| check_transaction_with_options(black_box(&tx), &runs))
+    });
+    group.finish();
+}
+
+criterion_group!(benches, extraction, transaction);
+criterion_main!(benches);
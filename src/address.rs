@@ -0,0 +1,16 @@
+use bitcoin::{Address, Script};
+
+use crate::Network;
+
+/// The address paying to `script` on `network`, for the standard output
+/// types (p2pkh, p2sh and segwit programs such as p2wpkh, p2wsh and p2tr).
+pub fn script_address(script: &[u8], network: Network) -> Option<String> {
+    let network = match network {
+        Network::Mainnet => bitcoin::Network::Bitcoin,
+        Network::Testnet => bitcoin::Network::Testnet,
+        Network::Signet => bitcoin::Network::Signet,
+    };
+    Address::from_script(Script::from_bytes(script), network)
+        .ok()
+        .map(|address| address.to_string())
+}
@@ -0,0 +1,144 @@
+use std::env;
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::PathBuf;
+
+use tracing::{debug, warn};
+
+/// On-disk cache of API responses, stored verbatim so they can be parsed
+/// again if the response format or the parser changes.
+#[derive(Clone, Debug)]
+pub struct Cache {
+    dir: PathBuf,
+    /// Ignore cached entries and overwrite them with fresh responses.
+    refresh: bool,
+}
+
+impl Cache {
+    pub fn new(dir: PathBuf, refresh: bool) -> Self {
+        Cache { dir, refresh }
+    }
+
+    /// `$XDG_CACHE_HOME/bitcoin_message_finder`, falling back to
+    /// `~/.cache/bitcoin_message_finder`.
+    pub fn default_dir() -> Option<PathBuf> {
+        let base = match env::var_os("XDG_CACHE_HOME") {
+            Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+            _ => PathBuf::from(env::var_os("HOME")?).join(".cache"),
+        };
+        Some(base.join(env!("CARGO_PKG_NAME")))
+    }
+
+    /// Whether a response is cached. A block height can name another block
+    /// after a reorg, so only responses keyed by a block hash or txid are,
+    /// and height lookups are always fetched again.
+    fn caches(url: &str) -> bool {
+        !url.contains("/block-height/")
+    }
+
+    /// Cache file for a request URL. The URL names the block hash or txid, so
+    /// it doubles as the cache key.
+    fn path(&self, url: &str) -> PathBuf {
+        let key = url
+            .trim_start_matches("https://")
+            .chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() || c == '.' || c == '-' {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .collect::<String>();
+        self.dir.join(key)
+    }
+
+    pub fn get(&self, url: &str) -> Option<String> {
+        if self.refresh || !Self::caches(url) {
+            return None;
+        }
+        let body = fs::read_to_string(self.path(url)).ok()?;
+        debug!(url, "cache hit");
+        Some(body)
+    }
+
+    /// Opens a cached response to be read incrementally, like [`Cache::get`].
+    pub fn open(&self, url: &str) -> Option<File> {
+        if self.refresh || !Self::caches(url) {
+            return None;
+        }
+        let file = File::open(self.path(url)).ok()?;
+        debug!(url, "cache hit");
+        Some(file)
+    }
+
+    /// Starts storing a response body as it is read. It only replaces the
+    /// cached entry once [`CacheWriter::commit`] is called, so a body that
+    /// fails to arrive or to parse is never cached.
+    pub fn writer(&self, url: &str) -> Option<CacheWriter> {
+        if !Self::caches(url) {
+            return None;
+        }
+        let path = self.path(url);
+        let mut partial = path.clone().into_os_string();
+        partial.push(".partial");
+        let partial = PathBuf::from(partial);
+        match fs::create_dir_all(&self.dir).and_then(|_| File::create(&partial)) {
+            Ok(file) => Some(CacheWriter {
+                file: Some(file),
+                partial,
+                path,
+            }),
+            Err(e) => {
+                warn!(path = %partial.display(), error = %e, "failed to write cache");
+                None
+            }
+        }
+    }
+
+    /// Stores a response body. Failing to write the cache is not fatal.
+    pub fn put(&self, url: &str, body: &str) {
+        if !Self::caches(url) {
+            return;
+        }
+        let path = self.path(url);
+        if let Err(e) = fs::create_dir_all(&self.dir).and_then(|_| fs::write(&path, body)) {
+            warn!(path = %path.display(), error = %e, "failed to write cache");
+        }
+    }
+}
+
+/// A cache entry being written, see [`Cache::writer`]. Dropping it without
+/// committing discards what was written.
+pub struct CacheWriter {
+    /// `None` once writing failed.
+    file: Option<File>,
+    partial: PathBuf,
+    path: PathBuf,
+}
+
+impl CacheWriter {
+    /// Appends to the body. Failing to write abandons the entry.
+    pub fn write(&mut self, bytes: &[u8]) {
+        if let Some(Err(e)) = self.file.as_mut().map(|file| file.write_all(bytes)) {
+            warn!(path = %self.partial.display(), error = %e, "failed to write cache");
+            self.file = None;
+        }
+    }
+
+    /// Stores the body written so far as the cached response.
+    pub fn commit(mut self) {
+        if self.file.take().is_some() {
+            if let Err(e) = fs::rename(&self.partial, &self.path) {
+                warn!(path = %self.path.display(), error = %e, "failed to write cache");
+            }
+        }
+    }
+}
+
+impl Drop for CacheWriter {
+    fn drop(&mut self) {
+        // A committed entry has been renamed away already.
+        let _ = fs::remove_file(&self.partial);
+    }
+}
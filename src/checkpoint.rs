@@ -0,0 +1,46 @@
+use std::fs;
+use std::io;
+
+use bitcoin_message_finder::AppError;
+
+/// A file holding the highest block height a range scan has completed, so an
+/// interrupted scan can resume after it.
+pub struct Checkpoint {
+    path: String,
+}
+
+impl Checkpoint {
+    pub fn new(path: &str) -> Self {
+        Checkpoint {
+            path: path.to_string(),
+        }
+    }
+
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// The recorded height, or `None` if there is no checkpoint yet.
+    pub fn load(&self) -> Result<Option<u64>, AppError> {
+        let text = match fs::read_to_string(&self.path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+        text.trim().parse().map(Some).map_err(|_| {
+            AppError::InvalidInput(format!(
+                "Checkpoint {} does not contain a block height.",
+                self.path
+            ))
+        })
+    }
+
+    /// Records `height` as completed. The file is replaced by a rename, so a
+    /// crash leaves either the old or the new height behind.
+    pub fn save(&self, height: u64) -> Result<(), AppError> {
+        let tmp = format!("{}.tmp", self.path);
+        fs::write(&tmp, format!("{}\n", height))?;
+        fs::rename(&tmp, &self.path)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,443 @@
+use std::io;
+
+use bitcoin_message_finder::{Backend, Encoding, MessageKind, MessageLocation, Network, XorKey};
+use chrono::{DateTime, NaiveDate, NaiveDateTime, NaiveTime};
+use clap::builder::{PossibleValue, PossibleValuesParser, TypedValueParser};
+use clap::{ArgAction, Args, CommandFactory, Parser, Subcommand};
+use clap_complete::Shell;
+use regex::Regex;
+
+use crate::output::Format;
+use crate::template::Template;
+
+const ENV_HELP: &str = "Environment:
+  BMF_BACKEND, BMF_NETWORK and BMF_MIN_LENGTH set defaults for --backend, --network
+  and --min-length. The flags take precedence.";
+
+/// Finds hidden messages in Bitcoin blocks and transactions.
+#[derive(Parser)]
+#[command(version, after_help = ENV_HELP)]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
+    /// Print a completion script for SHELL and exit
+    #[arg(long, value_name = "SHELL")]
+    pub completions: Option<Shell>,
+
+    /// Print the JSON Schema of findings in JSON output and exit
+    #[arg(long)]
+    pub print_schema: bool,
+
+    #[command(flatten)]
+    pub options: Options,
+}
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// Scan a block by height or hash
+    Block {
+        /// Block height or hash
+        block: String,
+        #[command(flatten)]
+        select: Select,
+        #[command(flatten)]
+        preflight: Preflight,
+    },
+    /// Scan a block saved as JSON
+    File {
+        path: String,
+        #[command(flatten)]
+        select: Select,
+    },
+    /// Scan every *.json block file in a directory, in file name order
+    Dir { path: String },
+    /// Fetch and scan a single transaction
+    Tx {
+        /// Transaction hash
+        #[arg(required_unless_present = "stdin")]
+        txid: Option<String>,
+        /// Fetch and scan every txid read from stdin, one per line
+        #[arg(long, conflicts_with = "txid")]
+        stdin: bool,
+    },
+    /// Scan every block from START to END
+    Range {
+        /// First block height, or with --since-timestamp the last one
+        #[arg(required_unless_present = "since_timestamp")]
+        start: Option<String>,
+        /// Last block height
+        #[arg(
+            required_unless_present = "since_timestamp",
+            conflicts_with = "since_timestamp"
+        )]
+        end: Option<String>,
+        /// Start at the first block mined at or after DATETIME (RFC 3339,
+        /// YYYY-MM-DD in UTC or Unix seconds), and end at the one height
+        /// given or else the chain tip
+        #[arg(long, value_name = "DATETIME", value_parser = parse_timestamp)]
+        since_timestamp: Option<u64>,
+        /// Blocks fetched in parallel
+        // Kept low so range scans stay below blockchain.info's rate limits.
+        #[arg(long, value_name = "N", default_value_t = 2,
+              value_parser = clap::value_parser!(u64).range(1..))]
+        concurrency: u64,
+        /// Record completed heights in PATH and resume after them
+        #[arg(long, value_name = "PATH")]
+        checkpoint: Option<String>,
+        /// Print the download size and duration of the scan from block
+        /// sizes, without fetching the blocks
+        #[arg(long)]
+        estimate: bool,
+        /// After the scan, print the time spent fetching and scanning and the
+        /// N slowest blocks (10 if N is left out)
+        #[arg(long, value_name = "N", num_args = 0..=1, default_missing_value = "10")]
+        profile: Option<usize>,
+        #[command(flatten)]
+        preflight: Preflight,
+    },
+    /// Scan the blocks at a list of heights, each once
+    Heights {
+        /// Block heights, separated by commas or spaces
+        #[arg(required = true, value_delimiter = ',')]
+        heights: Vec<u64>,
+        /// Blocks fetched in parallel
+        #[arg(long, value_name = "N", default_value_t = 2,
+              value_parser = clap::value_parser!(u64).range(1..))]
+        concurrency: u64,
+        /// After the scan, print the time spent fetching and scanning and the
+        /// N slowest blocks (10 if N is left out)
+        #[arg(long, value_name = "N", num_args = 0..=1, default_missing_value = "10")]
+        profile: Option<usize>,
+        #[command(flatten)]
+        preflight: Preflight,
+    },
+    /// Poll the chain tip and scan every new block as it arrives
+    Watch {
+        /// Seconds between tip polls
+        #[arg(long, value_name = "N", default_value_t = 60)]
+        interval_secs: u64,
+    },
+}
+
+/// Which transactions of a block are scanned.
+#[derive(Args)]
+pub struct Select {
+    /// Scan transaction N without prompting
+    #[arg(long, value_name = "N")]
+    pub tx: Option<usize>,
+    /// Print the selected transaction as JSON before its findings
+    #[arg(long)]
+    pub dump_tx: bool,
+    /// Scan every transaction in the block
+    #[arg(long)]
+    pub all: bool,
+}
+
+/// What happens before blocks are fetched.
+#[derive(Args)]
+pub struct Preflight {
+    /// Don't check block heights against the chain tip first
+    #[arg(long)]
+    pub no_tip_check: bool,
+    /// Print the URLs the scan would fetch, without fetching them
+    #[arg(long)]
+    pub dry_run: bool,
+}
+
+/// Options shared by all subcommands.
+#[derive(Args)]
+pub struct Options {
+    // Source
+    /// Block data backend [default: blockchain]
+    #[arg(long, global = true, help_heading = "Source", value_parser = backend_parser())]
+    pub backend: Option<Backend>,
+    /// Bitcoin network, others than mainnet need --backend mempool [default: mainnet]
+    #[arg(long, global = true, help_heading = "Source", value_parser = network_parser())]
+    pub network: Option<Network>,
+    /// Fetch serialized blocks and decode them locally
+    #[arg(long, global = true, help_heading = "Source")]
+    pub raw: bool,
+    /// Neither read nor write the response cache (~/.cache)
+    #[arg(long, global = true, help_heading = "Source")]
+    pub no_cache: bool,
+    /// Refetch cached responses and update the cache
+    #[arg(long, global = true, help_heading = "Source")]
+    pub refresh: bool,
+
+    // Extraction
+    /// Report every printable run instead of whole fields
+    #[arg(long, global = true, help_heading = "Extraction")]
+    pub strings: bool,
+    /// Which bytes count as text [default: ascii]
+    #[arg(long, global = true, ignore_case = true, help_heading = "Extraction", value_parser = encoding_parser())]
+    pub encoding: Option<Encoding>,
+    /// Only inspect these comma-separated locations, e.g. op_return,coinbase
+    #[arg(long, global = true, value_name = "LOCATIONS", value_delimiter = ',',
+          help_heading = "Extraction", value_parser = parse_location)]
+    pub only: Option<Vec<MessageLocation>>,
+    /// Inspect every location except these comma-separated ones
+    #[arg(long, global = true, value_name = "LOCATIONS", value_delimiter = ',',
+          conflicts_with = "only", help_heading = "Extraction", value_parser = parse_location)]
+    pub exclude: Option<Vec<MessageLocation>>,
+    /// Print a hexdump of embedded files found by their magic bytes
+    #[arg(long, global = true, help_heading = "Extraction")]
+    pub hexdump: bool,
+    /// Also decode payloads that are UTF-16 little endian text
+    #[arg(long, global = true, help_heading = "Extraction")]
+    pub utf16: bool,
+    /// Also decode payloads XORed with the repeating key HEX
+    #[arg(long, global = true, value_name = "HEX", help_heading = "Extraction",
+          value_parser = parse_xor_key)]
+    pub xor_key: Vec<XorKey>,
+    /// Also try all single-byte XOR keys on payloads, reporting the most English result
+    #[arg(long, global = true, help_heading = "Extraction")]
+    pub xor_brute: bool,
+    /// Also try all Caesar shifts (ROT13 included) on messages that aren't English
+    #[arg(long, global = true, help_heading = "Extraction")]
+    pub caesar: bool,
+    /// Show the offset and N bytes of hex around each message
+    #[arg(long, global = true, value_name = "N", help_heading = "Extraction")]
+    pub context: Option<usize>,
+    /// Discard messages shorter than N characters
+    #[arg(long, global = true, value_name = "N", help_heading = "Extraction")]
+    pub min_length: Option<usize>,
+    /// With --strings, skip fields where runs cover less than R (0-1)
+    #[arg(long, global = true, value_name = "R", help_heading = "Extraction", value_parser = parse_ratio)]
+    pub min_printable_ratio: Option<f64>,
+    /// Also decode messages that look like base64
+    #[arg(long, global = true, help_heading = "Extraction")]
+    pub decode_base64: bool,
+    /// Also decode messages that are hex-encoded text, up to 3 levels
+    #[arg(long, global = true, help_heading = "Extraction")]
+    pub recursive_hex: bool,
+    /// Also report messages split across consecutive OP_RETURN outputs
+    #[arg(long, global = true, help_heading = "Extraction")]
+    pub reassemble: bool,
+    /// Annotate well-known messages such as the genesis headline
+    #[arg(long, global = true, help_heading = "Extraction")]
+    pub famous: bool,
+    /// Verify messages signed with a Bitcoin address
+    #[arg(long, global = true, help_heading = "Extraction")]
+    pub verify_signatures: bool,
+    /// Show the address of the output script of findings in outputs
+    #[arg(long, global = true, help_heading = "Extraction")]
+    pub addresses: bool,
+    /// Report output addresses containing PATTERN, e.g. a vanity prefix
+    #[arg(
+        long,
+        global = true,
+        value_name = "PATTERN",
+        help_heading = "Extraction"
+    )]
+    pub vanity: Option<String>,
+
+    // Filters
+    /// Only report messages containing TERM (scans all transactions)
+    #[arg(long, global = true, value_name = "TERM", help_heading = "Filters")]
+    pub search: Option<String>,
+    /// Match --search case-sensitively
+    #[arg(long, global = true, help_heading = "Filters")]
+    pub case_sensitive: bool,
+    /// Only report messages matching PATTERN (scans all transactions)
+    #[arg(long, global = true, value_name = "PATTERN", help_heading = "Filters", value_parser = parse_regex)]
+    pub regex: Option<Regex>,
+    /// Drop messages above BITS of entropy per character
+    #[arg(long, global = true, value_name = "BITS", help_heading = "Filters")]
+    pub max_entropy: Option<f64>,
+    /// Drop messages with an English score (0-1) below SCORE
+    #[arg(long, global = true, value_name = "SCORE", help_heading = "Filters")]
+    pub min_english: Option<f64>,
+    /// Only report messages containing a URL, an email address or invisible
+    /// characters, possible artwork or hash references
+    #[arg(long, global = true, help_heading = "Filters", value_parser = kind_parser())]
+    pub kind: Option<MessageKind>,
+
+    // Output
+    /// Output format
+    #[arg(long, global = true, default_value = "text", help_heading = "Output", value_parser = format_parser())]
+    pub format: Format,
+    /// List transactions without messages in --all mode
+    #[arg(long, global = true, help_heading = "Output")]
+    pub show_empty: bool,
+    /// Report each distinct message only once, with counts at the end
+    #[arg(long, global = true, help_heading = "Output")]
+    pub unique: bool,
+    /// Print only the findings, without headers or prompts
+    #[arg(short, long, global = true, help_heading = "Output")]
+    pub quiet: bool,
+    /// Report at most N findings per transaction
+    #[arg(long, global = true, value_name = "N", help_heading = "Output",
+          value_parser = clap::value_parser!(u64).range(1..))]
+    pub limit_per_tx: Option<u64>,
+    /// Print totals per location and the longest message at the end. JSON
+    /// output becomes an object with "findings" and "summary"
+    #[arg(long, global = true, help_heading = "Output")]
+    pub summary: bool,
+    /// Also summarize scripts shared by several inputs or outputs
+    #[arg(long, global = true, help_heading = "Output")]
+    pub clusters: bool,
+    /// Stop at the first finding and print only that one
+    #[arg(long, global = true, help_heading = "Output")]
+    pub first_only: bool,
+    /// Write each finding of text output as TEMPLATE, e.g. "{tx_hash} {location}
+    /// {message}", with {{ and }} for braces
+    #[arg(long, global = true, value_name = "TEMPLATE", help_heading = "Output",
+          value_parser = Template::parse)]
+    pub template: Option<Template>,
+    /// Disable colored output (also disabled by NO_COLOR)
+    #[arg(long, global = true, help_heading = "Output")]
+    pub no_color: bool,
+    /// Write results to PATH instead of stdout
+    #[arg(long, global = true, value_name = "PATH", help_heading = "Output")]
+    pub output: Option<String>,
+    /// Also insert findings into the SQLite database at PATH
+    #[arg(long, global = true, value_name = "PATH", help_heading = "Output")]
+    pub sqlite: Option<String>,
+    /// Save the bytes of embedded files and protocol data to files in DIR
+    #[arg(long, global = true, value_name = "DIR", help_heading = "Output")]
+    pub dump_dir: Option<String>,
+    /// With --dump-dir, also save the fields of text findings
+    #[arg(long, global = true, requires = "dump_dir", help_heading = "Output")]
+    pub dump_all: bool,
+    /// POST findings as JSON to URL, batched per block
+    #[arg(long, global = true, value_name = "URL", help_heading = "Output")]
+    pub webhook: Option<String>,
+    /// Log requests and parsing to stderr (-vv for dependencies too)
+    #[arg(short, long, global = true, action = ArgAction::Count, help_heading = "Output")]
+    pub verbose: u8,
+
+    // HTTP
+    /// Seconds before an HTTP request attempt is abandoned and retried
+    #[arg(
+        global = true,
+        long,
+        value_name = "N",
+        default_value_t = 30,
+        help_heading = "HTTP"
+    )]
+    pub timeout_secs: u64,
+    /// HTTP User-Agent [default: bitcoin_message_finder/<version>]
+    #[arg(long, global = true, value_name = "STRING", help_heading = "HTTP")]
+    pub user_agent: Option<String>,
+    /// Send KEY in the X-API-Key header of every request
+    #[arg(long, global = true, value_name = "KEY", help_heading = "HTTP")]
+    pub api_key: Option<String>,
+    /// HTTP or SOCKS5 proxy [default: HTTP_PROXY/HTTPS_PROXY]
+    #[arg(long, global = true, value_name = "URL", help_heading = "HTTP")]
+    pub proxy: Option<String>,
+    /// Send at most N requests per second, however many run concurrently
+    #[arg(
+        long,
+        global = true,
+        value_name = "N",
+        value_parser = parse_rate,
+        help_heading = "HTTP"
+    )]
+    pub max_requests_per_sec: Option<f64>,
+    /// Skip blocks larger than N bytes instead of loading them into memory
+    #[arg(long, global = true, value_name = "N", help_heading = "HTTP")]
+    pub max_block_bytes: Option<u64>,
+}
+
+fn backend_parser() -> impl TypedValueParser<Value = Backend> {
+    PossibleValuesParser::new([
+        PossibleValue::new("blockchain").alias("blockchain.info"),
+        PossibleValue::new("mempool").alias("mempool.space"),
+    ])
+    .map(|name| Backend::parse(&name).expect("listed backend"))
+}
+
+fn network_parser() -> impl TypedValueParser<Value = Network> {
+    PossibleValuesParser::new([
+        PossibleValue::new("mainnet").alias("bitcoin"),
+        PossibleValue::new("testnet"),
+        PossibleValue::new("signet"),
+    ])
+    .map(|name| Network::parse(&name).expect("listed network"))
+}
+
+fn encoding_parser() -> impl TypedValueParser<Value = Encoding> {
+    PossibleValuesParser::new([
+        PossibleValue::new("ascii"),
+        PossibleValue::new("latin1").aliases(["latin-1", "iso-8859-1"]),
+        PossibleValue::new("utf8").alias("utf-8"),
+    ])
+    .map(|name| Encoding::parse(&name).expect("listed encoding"))
+}
+
+fn kind_parser() -> impl TypedValueParser<Value = MessageKind> {
+    PossibleValuesParser::new(["url", "email", "steganography", "artwork", "hash_reference"])
+        .map(|name| MessageKind::parse(&name).expect("listed kind"))
+}
+
+fn format_parser() -> impl TypedValueParser<Value = Format> {
+    PossibleValuesParser::new(["text", "json", "ndjson", "csv"])
+        .map(|name| Format::parse(&name).expect("listed format"))
+}
+
+fn parse_location(name: &str) -> Result<MessageLocation, String> {
+    MessageLocation::parse(name).ok_or_else(|| {
+        let names: Vec<_> = MessageLocation::ALL.iter().map(|l| l.name()).collect();
+        format!("unknown location, expected one of {}", names.join(", "))
+    })
+}
+
+fn parse_xor_key(key: &str) -> Result<XorKey, String> {
+    let key = key.strip_prefix("0x").unwrap_or(key);
+    hex::decode(key)
+        .ok()
+        .and_then(XorKey::new)
+        .ok_or_else(|| "expected a non-empty hex key".to_string())
+}
+
+fn parse_ratio(value: &str) -> Result<f64, String> {
+    value
+        .parse()
+        .ok()
+        .filter(|ratio| (0.0..=1.0).contains(ratio))
+        .ok_or_else(|| "expected a number from 0 to 1".to_string())
+}
+
+fn parse_rate(value: &str) -> Result<f64, String> {
+    value
+        .parse()
+        .ok()
+        .filter(|rate: &f64| rate.is_finite() && *rate > 0.0)
+        .ok_or_else(|| "expected a positive number".to_string())
+}
+
+/// Seconds since the Unix epoch of an RFC 3339 datetime, a UTC date or
+/// datetime without offset, or a plain number of seconds.
+fn parse_timestamp(value: &str) -> Result<u64, String> {
+    if let Ok(secs) = value.parse::<u64>() {
+        return Ok(secs);
+    }
+    let naive = |format| NaiveDateTime::parse_from_str(value, format).map(|dt| dt.and_utc());
+    let datetime = DateTime::parse_from_rfc3339(value)
+        .map(|dt| dt.to_utc())
+        .or_else(|_| naive("%Y-%m-%dT%H:%M:%S"))
+        .or_else(|_| naive("%Y-%m-%d %H:%M:%S"))
+        .or_else(|_| {
+            NaiveDate::parse_from_str(value, "%Y-%m-%d")
+                .map(|date| date.and_time(NaiveTime::MIN).and_utc())
+        })
+        .map_err(|_| "expected a date like 2024-01-01 or an RFC 3339 datetime".to_string())?;
+    u64::try_from(datetime.timestamp()).map_err(|_| "expected a date after 1970".to_string())
+}
+
+fn parse_regex(pattern: &str) -> Result<Regex, regex::Error> {
+    Regex::new(pattern)
+}
+
+/// The full `--help` text.
+pub fn usage() -> String {
+    Cli::command().render_help().to_string()
+}
+
+/// Writes the completion script for `shell` to stdout.
+pub fn print_completions(shell: Shell) {
+    let mut command = Cli::command();
+    let name = command.get_name().to_string();
+    clap_complete::generate(shell, &mut command, name, &mut io::stdout());
+}
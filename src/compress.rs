@@ -0,0 +1,28 @@
+use std::io::Read;
+
+use flate2::read::{GzDecoder, ZlibDecoder};
+
+/// Decompressed payloads larger than this are discarded rather than scanned,
+/// so a tiny decompression bomb cannot exhaust memory.
+pub const MAX_DECOMPRESSED_SIZE: u64 = 1024 * 1024;
+
+/// Decompresses a payload of the given [`crate::detect_file_type`] kind.
+/// Returns `None` for other kinds, corrupt data or output above
+/// [`MAX_DECOMPRESSED_SIZE`].
+pub fn decompress(data: &[u8], file_type: &str) -> Option<Vec<u8>> {
+    let reader: Box<dyn Read + '_> = match file_type {
+        "gzip" => Box::new(GzDecoder::new(data)),
+        "zlib" => Box::new(ZlibDecoder::new(data)),
+        _ => return None,
+    };
+
+    let mut output = Vec::new();
+    reader
+        .take(MAX_DECOMPRESSED_SIZE + 1)
+        .read_to_end(&mut output)
+        .ok()?;
+    if output.len() as u64 > MAX_DECOMPRESSED_SIZE {
+        return None;
+    }
+    Some(output)
+}
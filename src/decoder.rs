@@ -0,0 +1,167 @@
+use std::fmt;
+use std::sync::Arc;
+
+use crate::{english_score, message_from_bytes};
+
+/// A custom encoding tried on every payload, in addition to the built-in
+/// printable, base64 and hex decoding. Register implementations in a
+/// [`DecoderRegistry`] to have the scanner run them.
+pub trait Decoder: Send + Sync {
+    /// Recorded as the [`crate::Finding::decoder`] of messages it decodes.
+    fn name(&self) -> &str;
+
+    /// The message hidden in `bytes`, or `None` if they don't use this encoding.
+    fn try_decode(&self, bytes: &[u8]) -> Option<String>;
+}
+
+/// The custom decoders a scan runs, in registration order.
+#[derive(Clone, Default)]
+pub struct DecoderRegistry {
+    decoders: Vec<Arc<dyn Decoder>>,
+}
+
+impl DecoderRegistry {
+    pub fn new() -> Self {
+        DecoderRegistry::default()
+    }
+
+    pub fn register(&mut self, decoder: impl Decoder + 'static) {
+        self.decoders.push(Arc::new(decoder));
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.decoders.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &dyn Decoder> {
+        self.decoders.iter().map(|decoder| decoder.as_ref())
+    }
+}
+
+impl fmt::Debug for DecoderRegistry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list()
+            .entries(self.iter().map(|decoder| decoder.name()))
+            .finish()
+    }
+}
+
+/// Text stored as UTF-16 little endian, as written by Windows tools.
+#[derive(Clone, Copy, Debug)]
+pub struct Utf16Le;
+
+impl Decoder for Utf16Le {
+    fn name(&self) -> &str {
+        "utf16le"
+    }
+
+    fn try_decode(&self, bytes: &[u8]) -> Option<String> {
+        if bytes.is_empty() || !bytes.len().is_multiple_of(2) {
+            return None;
+        }
+        let units: Vec<u16> = bytes
+            .chunks_exact(2)
+            .map(|pair| u16::from_le_bytes([pair[0], pair[1]]))
+            .collect();
+        String::from_utf16(&units)
+            .ok()
+            .filter(|text| !text.chars().any(char::is_control))
+    }
+}
+
+/// Printable ASCII XORed with a repeating key. Payloads that are printable
+/// as they are are left to the built-in extraction.
+#[derive(Clone, Debug)]
+pub struct XorKey {
+    key: Vec<u8>,
+    name: String,
+}
+
+impl XorKey {
+    /// Returns `None` for an empty key.
+    pub fn new(key: Vec<u8>) -> Option<Self> {
+        if key.is_empty() {
+            return None;
+        }
+        let name = format!("xor {}", hex::encode(&key));
+        Some(XorKey { key, name })
+    }
+}
+
+impl Decoder for XorKey {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn try_decode(&self, bytes: &[u8]) -> Option<String> {
+        if message_from_bytes(bytes).is_some() {
+            return None;
+        }
+        let decoded: Vec<u8> = bytes
+            .iter()
+            .zip(self.key.iter().cycle())
+            .map(|(b, k)| b ^ k)
+            .collect();
+        message_from_bytes(&decoded)
+    }
+}
+
+/// Lowest English score a [`xor_brute_force`] candidate needs.
+pub const XOR_MIN_ENGLISH: f64 = 0.4;
+
+/// Tries every single-byte XOR key on a payload that isn't printable as it
+/// is, and returns the key whose printable result reads most like English,
+/// if it scores at least [`XOR_MIN_ENGLISH`].
+pub fn xor_brute_force(bytes: &[u8]) -> Option<(u8, String)> {
+    if message_from_bytes(bytes).is_some() {
+        return None;
+    }
+    let mut best: Option<(u8, String, f64)> = None;
+    let mut decoded = Vec::with_capacity(bytes.len());
+    for key in 1..=u8::MAX {
+        decoded.clear();
+        decoded.extend(bytes.iter().map(|b| b ^ key));
+        let Some(message) = message_from_bytes(&decoded) else {
+            continue;
+        };
+        let score = english_score(&message);
+        if score >= XOR_MIN_ENGLISH && best.as_ref().is_none_or(|(_, _, top)| score > *top) {
+            best = Some((key, message, score));
+        }
+    }
+    best.map(|(key, message, _)| (key, message))
+}
+
+/// Lowest English score a [`caesar_brute_force`] result needs, and the score
+/// below which a message is worth trying shifts on.
+pub const CAESAR_MIN_ENGLISH: f64 = 0.5;
+
+/// Rotates the ASCII letters of `s` by `shift` places, keeping their case.
+pub fn caesar_shift(s: &str, shift: u8) -> String {
+    s.chars()
+        .map(|c| {
+            let base = match c {
+                'a'..='z' => b'a',
+                'A'..='Z' => b'A',
+                _ => return c,
+            };
+            ((c as u8 - base + shift % 26) % 26 + base) as char
+        })
+        .collect()
+}
+
+/// Undoes a Caesar cipher on a message that doesn't read like English, trying
+/// all 25 shifts. Returns the shift the message was encoded with (13 for
+/// ROT13) and the decoded text whose English score is highest, if it reaches
+/// [`CAESAR_MIN_ENGLISH`].
+pub fn caesar_brute_force(message: &str) -> Option<(u8, String)> {
+    if english_score(message) >= CAESAR_MIN_ENGLISH {
+        return None;
+    }
+    (1..26u8)
+        .map(|shift| (shift, caesar_shift(message, 26 - shift)))
+        .map(|(shift, decoded)| (english_score(&decoded), shift, decoded))
+        .filter(|(score, _, _)| *score >= CAESAR_MIN_ENGLISH)
+        .max_by(|a, b| a.0.total_cmp(&b.0).then(b.1.cmp(&a.1)))
+        .map(|(_, shift, decoded)| (shift, decoded))
+}
@@ -0,0 +1,86 @@
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use bitcoin_message_finder::{AppError, Finding};
+
+/// Saves the bytes behind findings to files, so embedded images and archives
+/// can be opened.
+pub struct Dumper {
+    dir: PathBuf,
+    /// Also save text findings, as the bytes of the field they came from.
+    all: bool,
+}
+
+impl Dumper {
+    /// Creates `dir` if it does not exist yet.
+    pub fn new(dir: &str, all: bool) -> Result<Self, AppError> {
+        fs::create_dir_all(dir)?;
+        Ok(Dumper {
+            dir: PathBuf::from(dir),
+            all,
+        })
+    }
+
+    /// Writes the payload of `finding` to
+    /// `DIR/{tx_hash}_{location}_{index}.{extension}`, numbering the name
+    /// when it is taken. Returns where it went, or `None` for text findings
+    /// unless all findings are saved.
+    pub fn dump(&self, finding: &Finding) -> Result<Option<PathBuf>, AppError> {
+        let bytes = match &finding.payload {
+            Some(payload) => payload.clone(),
+            None if self.all => hex::decode(&finding.raw_hex).unwrap_or_default(),
+            None => return Ok(None),
+        };
+        let stem = sanitize(&format!(
+            "{}_{}_{}",
+            finding.tx_hash,
+            finding.location.name(),
+            finding.vin_or_vout_index
+        ));
+        let extension = finding.file_type.as_deref().map_or("bin", extension);
+        Ok(Some(create_new(&self.dir, &stem, extension, &bytes)?))
+    }
+}
+
+/// The usual extension of a [`Finding::file_type`].
+fn extension(file_type: &str) -> &str {
+    match file_type {
+        "jpeg" => "jpg",
+        "gzip" => "gz",
+        "bzip2" => "bz2",
+        other => other,
+    }
+}
+
+/// Keeps names to characters that are safe in any file system, so API data
+/// cannot reach outside the directory.
+fn sanitize(name: &str) -> String {
+    name.chars()
+        .map(|c| match c {
+            'a'..='z' | 'A'..='Z' | '0'..='9' | '_' | '-' => c,
+            _ => '_',
+        })
+        .collect()
+}
+
+/// Writes `bytes` to `STEM.EXT` in `dir`, or to `STEM_1.EXT`, `STEM_2.EXT`
+/// and so on if the name exists, never overwriting a file.
+fn create_new(dir: &Path, stem: &str, extension: &str, bytes: &[u8]) -> io::Result<PathBuf> {
+    for n in 0.. {
+        let name = match n {
+            0 => format!("{}.{}", stem, extension),
+            n => format!("{}_{}.{}", stem, n, extension),
+        };
+        let path = dir.join(name);
+        match OpenOptions::new().write(true).create_new(true).open(&path) {
+            Ok(mut file) => {
+                file.write_all(bytes)?;
+                return Ok(path);
+            }
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists => continue,
+            Err(e) => return Err(e),
+        }
+    }
+    unreachable!("file names ran out")
+}
@@ -0,0 +1,60 @@
+use std::io;
+use std::time::Duration;
+
+use thiserror::Error;
+
+/// Errors produced while fetching a block from a backend.
+#[derive(Debug, Error)]
+pub enum FetchError {
+    #[error("HTTP request failed: {0}")]
+    Http(#[from] reqwest::Error),
+    #[error("API returned non-JSON (status {status}){}: {snippet}", rate_limit_hint(*status))]
+    NonJson { status: u16, snippet: String },
+    #[error("API returned status {status}{}: {snippet}", rate_limit_hint(*status))]
+    Status { status: u16, snippet: String },
+    #[error("Block at {url} is larger than the limit of {limit} bytes")]
+    TooLarge { url: String, limit: u64 },
+    #[error("Request to {url} timed out after {}s", after.as_secs_f64())]
+    Timeout { url: String, after: Duration },
+    #[error("Invalid proxy URL {url}: {source}")]
+    InvalidProxy { url: String, source: reqwest::Error },
+    #[error("Invalid HTTP header value: {0}")]
+    Header(#[from] reqwest::header::InvalidHeaderValue),
+    #[error("No blocks found for {0}")]
+    NoBlocks(String),
+    #[error("Invalid block JSON: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("Cannot read {path}: {source}")]
+    File { path: String, source: io::Error },
+    #[error("Invalid raw block: {0}")]
+    RawBlock(String),
+    #[error("{0}")]
+    Unsupported(String),
+}
+
+fn rate_limit_hint(status: u16) -> &'static str {
+    if status == 429 {
+        ", you may be rate limited"
+    } else {
+        ""
+    }
+}
+
+/// Top-level error type for the command line tool.
+#[derive(Debug, Error)]
+pub enum AppError {
+    #[error(transparent)]
+    Fetch(#[from] FetchError),
+    #[error("JSON error: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("I/O error: {0}")]
+    Io(#[from] io::Error),
+    #[error("Cannot write output file {path}: {source}")]
+    OutputFile { path: String, source: io::Error },
+    #[error("Database error: {0}")]
+    Database(#[from] rusqlite::Error),
+    #[error("{0}")]
+    Usage(String),
+    #[error("{0}")]
+    InvalidInput(String),
+}
@@ -0,0 +1,67 @@
+use std::fmt;
+use std::time::Duration;
+
+use bitcoin_message_finder::{Backend, BlockSize};
+
+/// Assumed round trip of one request, which is what limits sequential fetches.
+const REQUEST_LATENCY: Duration = Duration::from_millis(500);
+/// Assumed download speed in bytes per second, shared by parallel fetches.
+const BANDWIDTH: f64 = 5_000_000.0;
+
+/// What scanning a range of blocks would download, printed by `--estimate`.
+pub struct Estimate {
+    blocks: usize,
+    bytes: u64,
+    requests: usize,
+    duration: Duration,
+}
+
+impl Estimate {
+    /// Estimates fetching `sizes` with `backend`, `concurrency` blocks at a
+    /// time and at most `max_requests_per_sec` requests per second.
+    pub fn new(
+        sizes: &[BlockSize],
+        backend: Backend,
+        raw: bool,
+        concurrency: usize,
+        max_requests_per_sec: Option<f64>,
+    ) -> Self {
+        let bytes = sizes.iter().map(|block| block.size).sum();
+        let requests = sizes
+            .iter()
+            .map(|block| backend.requests_per_block(raw, block.tx_count))
+            .sum();
+        let waiting = REQUEST_LATENCY.as_secs_f64() * requests as f64 / concurrency.max(1) as f64;
+        let mut seconds = waiting + bytes as f64 / BANDWIDTH;
+        if let Some(rate) = max_requests_per_sec {
+            seconds = seconds.max(requests as f64 / rate);
+        }
+        Estimate {
+            blocks: sizes.len(),
+            bytes,
+            requests,
+            duration: Duration::from_secs_f64(seconds),
+        }
+    }
+}
+
+fn megabytes(bytes: u64) -> f64 {
+    bytes as f64 / 1_000_000.0
+}
+
+impl fmt::Display for Estimate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Blocks: {}", self.blocks)?;
+        // JSON responses are larger than the serialized blocks they describe.
+        writeln!(f, "Block data: {:.1} MB", megabytes(self.bytes))?;
+        writeln!(f, "Requests: {}", self.requests)?;
+        let secs = self.duration.as_secs();
+        writeln!(
+            f,
+            "Estimated time: {}h {:02}m {:02}s",
+            secs / 3600,
+            secs / 60 % 60,
+            secs % 60
+        )
+    }
+}
@@ -0,0 +1,28 @@
+/// Well-known on-chain messages, matched case-insensitively against findings.
+const FAMOUS_MESSAGES: &[(&str, &str)] = &[
+    (
+        "The Times 03/Jan/2009 Chancellor on brink of second bailout for banks",
+        "Satoshi's headline in the genesis block coinbase (block 0)",
+    ),
+    (
+        "Chancellor on brink of second bailout",
+        "Quote of the genesis block headline",
+    ),
+    (
+        "SASSAMA",
+        "Dan Kaminsky's memorial for Len Sassaman (block 138725, 2011)",
+    ),
+    (
+        "Bitcoin: A Peer-to-Peer Electronic Cash System",
+        "Title of the Bitcoin whitepaper",
+    ),
+];
+
+/// Returns the significance of a well-known message, if `message` contains one.
+pub fn famous_message(message: &str) -> Option<&'static str> {
+    let message = message.to_lowercase();
+    FAMOUS_MESSAGES
+        .iter()
+        .find(|(pattern, _)| message.contains(&pattern.to_lowercase()))
+        .map(|(_, description)| *description)
+}
@@ -0,0 +1,112 @@
+use std::ops::Range;
+
+use regex::Regex;
+
+use crate::{Finding, MessageKind};
+
+/// Criteria a [`Finding`] must meet to be reported.
+#[derive(Clone, Debug, Default)]
+pub struct FindingFilter {
+    /// Only keep messages containing this term.
+    pub search: Option<String>,
+    /// Match `search` case-sensitively. Off by default.
+    pub case_sensitive: bool,
+    /// Only keep messages matching this pattern.
+    pub regex: Option<Regex>,
+    /// Drop messages whose entropy (bits per character) exceeds this value.
+    pub max_entropy: Option<f64>,
+    /// Drop messages whose English score is below this value.
+    pub min_english: Option<f64>,
+    /// Only keep messages of this kind.
+    pub kind: Option<MessageKind>,
+}
+
+impl FindingFilter {
+    /// Whether a search term or pattern restricts the reported messages.
+    pub fn has_pattern(&self) -> bool {
+        self.search.is_some() || self.regex.is_some()
+    }
+
+    /// Whether any criterion looks at the text of a message.
+    fn has_text_criteria(&self) -> bool {
+        self.has_pattern() || self.max_entropy.is_some() || self.min_english.is_some()
+    }
+
+    /// Byte ranges of `message` matched by the search term and pattern.
+    pub fn match_ranges(&self, message: &str) -> Vec<Range<usize>> {
+        let mut ranges = Vec::new();
+        if let Some(term) = self.search.as_deref().filter(|term| !term.is_empty()) {
+            if self.case_sensitive {
+                ranges.extend(message.match_indices(term).map(|(i, m)| i..i + m.len()));
+            } else {
+                // Lowercasing can change the length of a character, so each
+                // byte of the haystack remembers the character it came from.
+                let mut haystack = String::new();
+                let mut sources = Vec::new();
+                for (source, lower) in fold_case(message) {
+                    haystack.push(lower);
+                    sources.extend(std::iter::repeat_n(source, lower.len_utf8()));
+                }
+                let needle: String = fold_case(term).map(|(_, lower)| lower).collect();
+                ranges.extend(
+                    haystack
+                        .match_indices(&needle)
+                        .map(|(i, m)| sources[i].start..sources[i + m.len() - 1].end),
+                );
+            }
+        }
+        if let Some(regex) = &self.regex {
+            ranges.extend(regex.find_iter(message).map(|m| m.range()));
+        }
+        ranges.sort_by_key(|range| range.start);
+        ranges
+    }
+
+    /// Whether `finding` meets every criterion. Findings without a message
+    /// fail the criteria on text, which they have none of.
+    pub fn matches(&self, finding: &Finding) -> bool {
+        if self.has_text_criteria() && !finding.has_message() {
+            return false;
+        }
+        if let Some(term) = &self.search {
+            let found = if self.case_sensitive {
+                finding.message.contains(term.as_str())
+            } else {
+                let lower = |text| fold_case(text).map(|(_, lower)| lower).collect::<String>();
+                lower(&finding.message).contains(&lower(term))
+            };
+            if !found {
+                return false;
+            }
+        }
+        if let Some(regex) = &self.regex {
+            if !regex.is_match(&finding.message) {
+                return false;
+            }
+        }
+        if let Some(max_entropy) = self.max_entropy {
+            if finding.entropy > max_entropy {
+                return false;
+            }
+        }
+        if let Some(min_english) = self.min_english {
+            if finding.english_score < min_english {
+                return false;
+            }
+        }
+        if self.kind.is_some() && finding.kind != self.kind {
+            return false;
+        }
+        true
+    }
+}
+
+/// Lowercases `text` one character at a time, pairing each resulting
+/// character with the byte range of the character it came from. Searches
+/// ignoring case compare these, so their matches map back onto `text`.
+fn fold_case(text: &str) -> impl Iterator<Item = (Range<usize>, char)> + '_ {
+    text.char_indices().flat_map(|(i, c)| {
+        c.to_lowercase()
+            .map(move |lower| (i..i + c.len_utf8(), lower))
+    })
+}
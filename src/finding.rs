@@ -0,0 +1,303 @@
+use std::fmt;
+
+use schemars::{JsonSchema, Schema};
+use serde::Serialize;
+
+use crate::kind::MessageKind;
+use crate::signature::SignatureCheck;
+
+/// The part of a transaction a message was found in.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum MessageLocation {
+    Coinbase,
+    /// The coinbase after its leading BIP34 height push.
+    CoinbaseTag,
+    ScriptSig,
+    Witness,
+    Inscription,
+    OpReturn,
+    ScriptPubKey,
+    /// Data stuffed into the fake public keys of a bare multisig output.
+    P2msData,
+    /// The address of an output, reported when it matches
+    /// [`crate::ScanOptions::vanity`].
+    Address,
+}
+
+impl MessageLocation {
+    pub const ALL: [MessageLocation; 9] = [
+        MessageLocation::Coinbase,
+        MessageLocation::CoinbaseTag,
+        MessageLocation::ScriptSig,
+        MessageLocation::Witness,
+        MessageLocation::Inscription,
+        MessageLocation::OpReturn,
+        MessageLocation::ScriptPubKey,
+        MessageLocation::P2msData,
+        MessageLocation::Address,
+    ];
+
+    /// The snake_case name used in JSON output, e.g. `op_return`.
+    pub fn name(self) -> &'static str {
+        match self {
+            MessageLocation::Coinbase => "coinbase",
+            MessageLocation::CoinbaseTag => "coinbase_tag",
+            MessageLocation::ScriptSig => "script_sig",
+            MessageLocation::Witness => "witness",
+            MessageLocation::Inscription => "inscription",
+            MessageLocation::OpReturn => "op_return",
+            MessageLocation::ScriptPubKey => "script_pub_key",
+            MessageLocation::P2msData => "p2ms_data",
+            MessageLocation::Address => "address",
+        }
+    }
+
+    /// Whether messages here are found in an output script.
+    pub fn is_output(self) -> bool {
+        matches!(
+            self,
+            MessageLocation::OpReturn | MessageLocation::ScriptPubKey | MessageLocation::P2msData
+        )
+    }
+
+    /// Parses a [`MessageLocation::name`], ignoring case and underscores, so
+    /// `scriptpubkey` and `OP_RETURN` work too.
+    pub fn parse(name: &str) -> Option<MessageLocation> {
+        let wanted = name.to_ascii_lowercase().replace(['_', '-'], "");
+        MessageLocation::ALL
+            .into_iter()
+            .find(|location| location.name().replace('_', "") == wanted)
+    }
+}
+
+impl fmt::Display for MessageLocation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let label = match self {
+            MessageLocation::Coinbase => "Coinbase",
+            MessageLocation::CoinbaseTag => "Coinbase tag",
+            MessageLocation::ScriptSig => "ScriptSig",
+            MessageLocation::Witness => "Witness",
+            MessageLocation::Inscription => "Inscription",
+            MessageLocation::OpReturn => "OP_RETURN",
+            MessageLocation::ScriptPubKey => "ScriptPubKey",
+            MessageLocation::P2msData => "P2MS-data",
+            MessageLocation::Address => "Address",
+        };
+        f.write_str(label)
+    }
+}
+
+/// A message found in one field of a transaction.
+#[derive(Clone, Debug, PartialEq, Serialize, JsonSchema)]
+pub struct Finding {
+    pub tx_hash: String,
+    pub location: MessageLocation,
+    /// Index of the input (for coinbase, scriptSig and witness locations) or
+    /// output the field belongs to.
+    pub vin_or_vout_index: usize,
+    /// Index of the witness item, for [`MessageLocation::Witness`].
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub item_index: Option<usize>,
+    /// Hex of the field the message was extracted from.
+    pub raw_hex: String,
+    /// Set when the message is the result of decoding another message, e.g. `base64`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub decoder: Option<String>,
+    /// Empty for findings that are not text, like embedded files, see
+    /// [`Finding::has_message`].
+    pub message: String,
+    /// Shannon entropy of `message` in bits per character.
+    pub entropy: f64,
+    /// Fraction of `message` made up of common English words, see
+    /// [`crate::english_score`].
+    pub english_score: f64,
+    /// Mining pool identified from a coinbase message.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pool: Option<String>,
+    /// Unix timestamp embedded in a coinbase, as an RFC 3339 UTC datetime.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timestamp: Option<String>,
+    /// File format detected from magic bytes, see [`crate::detect_file_type`].
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file_type: Option<String>,
+    /// Length in bytes of the embedded file or protocol data the finding
+    /// stands for.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub payload_length: Option<usize>,
+    /// Number of near-identical data outputs behind an artwork finding.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data_outputs: Option<usize>,
+    /// Why the message is well known, set with [`crate::ScanOptions::famous`].
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub significance: Option<String>,
+    /// Meta-protocol an OP_RETURN payload belongs to, see
+    /// [`crate::identify_protocol`].
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub protocol: Option<String>,
+    /// Fraction of the field's bytes that belong to printable runs, set in
+    /// [`crate::ExtractMode::Runs`].
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub printable_ratio: Option<f64>,
+    /// Where the message sits in `raw_hex`, set with
+    /// [`crate::ScanOptions::context`].
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub context: Option<Context>,
+    /// Outcome of verifying a signed message, set with
+    /// [`crate::ScanOptions::verify_signatures`].
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signature: Option<SignatureCheck>,
+    /// Set when the message contains a URL or email address, see
+    /// [`crate::classify_message`], or invisible characters.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub kind: Option<MessageKind>,
+    /// The URL or email address that determined `kind`, or what the invisible
+    /// characters hide.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub kind_match: Option<String>,
+    /// Hexdump of an embedded file, set with [`crate::ScanOptions::hexdump`].
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hexdump: Option<String>,
+    /// Address of the output script, set with
+    /// [`crate::ScanOptions::addresses`] for standard output types.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub address: Option<String>,
+    /// How the body of an inscription was stored, for
+    /// [`MessageLocation::Inscription`].
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub inscription_span: Option<InscriptionSpan>,
+    /// Name of the block file the finding was read from, set when scanning a
+    /// directory of them.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source_file: Option<String>,
+    /// The bytes of an embedded file or protocol data, which have no
+    /// message. Not serialized.
+    #[serde(skip)]
+    pub payload: Option<Vec<u8>>,
+}
+
+/// The bytes around a message in its field.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, JsonSchema)]
+pub struct Context {
+    /// Byte offset of the message in `raw_hex`.
+    pub offset: usize,
+    /// Hex of the bytes right before the message.
+    pub before: String,
+    /// Hex of the bytes right after the message.
+    pub after: String,
+}
+
+/// Where the body of an inscription came from, see [`crate::Inscription`].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, JsonSchema)]
+pub struct InscriptionSpan {
+    /// Length of the reassembled body in bytes.
+    pub length: usize,
+    /// Indices of the witness items holding the envelope.
+    pub witness_items: Vec<usize>,
+    /// Number of data pushes the body was split into.
+    pub pushes: usize,
+}
+
+impl Finding {
+    /// JSON Schema of a finding as written by the JSON and NDJSON output.
+    pub fn schema() -> Schema {
+        schemars::schema_for!(Finding)
+    }
+
+    /// Where the message was found, e.g. `Witness[2] (base64)`.
+    pub fn label(&self) -> String {
+        let mut label = self.location.to_string();
+        if let Some(item_index) = self.item_index {
+            label.push_str(&format!("[{}]", item_index));
+        }
+        if let Some(decoder) = &self.decoder {
+            label.push_str(&format!(" ({})", decoder));
+        }
+        label
+    }
+
+    /// Whether the finding is text. Findings for embedded files, protocol data
+    /// and artwork have no message, which keeps them out of text searches and
+    /// scores.
+    pub fn has_message(&self) -> bool {
+        !self.message.is_empty()
+    }
+
+    /// Bracketed notes printed after the message, or an empty string.
+    pub fn annotations(&self) -> String {
+        let mut notes = String::new();
+        if let (Some(file_type), Some(length)) = (&self.file_type, self.payload_length) {
+            notes.push_str(&format!(" [{} file, {} bytes]", file_type, length));
+        }
+        if let (Some(protocol), Some(length)) = (&self.protocol, self.payload_length) {
+            notes.push_str(&format!(" [{} data, {} bytes]", protocol, length));
+        }
+        if let Some(count) = self.data_outputs {
+            notes.push_str(&format!(" [{} data outputs]", count));
+        }
+        if let Some(timestamp) = &self.timestamp {
+            notes.push_str(&format!(" [timestamp {}]", timestamp));
+        }
+        match (self.kind, &self.kind_match) {
+            (Some(kind), Some(matched)) if *matched != self.message => {
+                notes.push_str(&format!(" [{} {}]", kind, matched));
+            }
+            (Some(kind), _) => notes.push_str(&format!(" [{}]", kind)),
+            (None, _) => {}
+        }
+        if let Some(span) = &self.inscription_span {
+            let items: Vec<String> = span.witness_items.iter().map(usize::to_string).collect();
+            notes.push_str(&format!(
+                " [{} bytes in {} pushes, witness {} {}]",
+                span.length,
+                span.pushes,
+                if items.len() == 1 { "item" } else { "items" },
+                items.join(", ")
+            ));
+        }
+        if let Some(address) = &self.address {
+            notes.push_str(&format!(" [address {}]", address));
+        }
+        if let Some(significance) = &self.significance {
+            notes.push_str(&format!(" [famous: {}]", significance));
+        }
+        if let Some(signature) = &self.signature {
+            let verdict = if signature.valid { "valid" } else { "INVALID" };
+            notes.push_str(&format!(
+                " [signature {} for {}]",
+                verdict, signature.address
+            ));
+        }
+        if let Some(context) = &self.context {
+            let or_dash = |hex: &str| {
+                if hex.is_empty() {
+                    "-".to_string()
+                } else {
+                    hex.to_string()
+                }
+            };
+            notes.push_str(&format!(
+                " [offset {}, before {}, after {}]",
+                context.offset,
+                or_dash(&context.before),
+                or_dash(&context.after)
+            ));
+        }
+        notes
+    }
+}
+
+impl fmt::Display for Finding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.has_message() {
+            true => write!(
+                f,
+                "{}: {}{}",
+                self.label(),
+                self.message,
+                self.annotations()
+            ),
+            false => write!(f, "{}:{}", self.label(), self.annotations()),
+        }
+    }
+}
@@ -0,0 +1,43 @@
+use std::fmt::Write;
+
+/// Bytes shown per line.
+const BYTES_PER_LINE: usize = 16;
+
+/// Longest payload prefix rendered by [`hexdump`].
+pub const HEXDUMP_MAX_BYTES: usize = 512;
+
+/// Renders `bytes` like `hexdump -C`: the offset, sixteen bytes in hex and
+/// the same bytes as ASCII, with dots for anything unprintable. Only the first
+/// [`HEXDUMP_MAX_BYTES`] are shown, followed by a note on how many were left
+/// out. Every line ends with a newline.
+pub fn hexdump(bytes: &[u8]) -> String {
+    let shown = &bytes[..bytes.len().min(HEXDUMP_MAX_BYTES)];
+    let mut dump = String::new();
+    for (line, chunk) in shown.chunks(BYTES_PER_LINE).enumerate() {
+        let _ = write!(dump, "{:08x} ", line * BYTES_PER_LINE);
+        for i in 0..BYTES_PER_LINE {
+            if i == BYTES_PER_LINE / 2 {
+                dump.push(' ');
+            }
+            match chunk.get(i) {
+                Some(b) => {
+                    let _ = write!(dump, " {:02x}", b);
+                }
+                None => dump.push_str("   "),
+            }
+        }
+        dump.push_str("  |");
+        dump.extend(chunk.iter().map(|&b| {
+            if b.is_ascii_graphic() || b == b' ' {
+                b as char
+            } else {
+                '.'
+            }
+        }));
+        dump.push_str("|\n");
+    }
+    if bytes.len() > shown.len() {
+        let _ = writeln!(dump, "... {} more bytes", bytes.len() - shown.len());
+    }
+    dump
+}
@@ -0,0 +1,106 @@
+use crate::script::{self, Instruction};
+
+const OP_IF: u8 = 0x63;
+const OP_ENDIF: u8 = 0x68;
+const OP_1: u8 = 0x51;
+const PROTOCOL_ID: &[u8] = b"ord";
+const CONTENT_TYPE_TAG: &[u8] = &[1];
+
+/// Content recovered from an Ordinals inscription envelope.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Inscription {
+    pub content_type: Option<String>,
+    pub body: Vec<u8>,
+    /// Indices of the witness items the envelope was read from, usually one.
+    pub witness_items: Vec<usize>,
+    /// Number of data pushes the body was split into.
+    pub pushes: usize,
+}
+
+impl Inscription {
+    pub fn is_text(&self) -> bool {
+        self.content_type
+            .as_deref()
+            .is_some_and(|content_type| content_type.starts_with("text/plain"))
+    }
+}
+
+/// Looks for an `OP_FALSE OP_IF "ord" ... OP_ENDIF` envelope in the witness
+/// items and returns the content type and concatenated body pushes. An
+/// envelope that is not closed by the end of its item continues in the next
+/// items, as long as they parse as scripts.
+pub fn extract_inscription(witness: &[String]) -> Option<Inscription> {
+    let items: Vec<Option<Vec<u8>>> = witness.iter().map(|item| hex::decode(item).ok()).collect();
+    let scripts: Vec<Option<Vec<Instruction<'_>>>> = items
+        .iter()
+        .map(|bytes| script::instructions(bytes.as_deref()?))
+        .collect();
+    (0..scripts.len()).find_map(|first| {
+        let instructions: Vec<(usize, &Instruction<'_>)> = scripts[first..]
+            .iter()
+            .map_while(Option::as_ref)
+            .enumerate()
+            .flat_map(|(offset, script)| script.iter().map(move |ins| (first + offset, ins)))
+            .collect();
+        parse_envelope(&instructions)
+    })
+}
+
+/// Parses the first envelope in `instructions`, each paired with the index of
+/// the witness item it comes from.
+fn parse_envelope(instructions: &[(usize, &Instruction<'_>)]) -> Option<Inscription> {
+    let start = instructions.windows(3).position(|w| {
+        *w[0].1 == Instruction::Push(&[])
+            && *w[1].1 == Instruction::Op(OP_IF)
+            && *w[2].1 == Instruction::Push(PROTOCOL_ID)
+    })?;
+
+    let mut fields = instructions[start + 3..].iter().map(|&(_, ins)| ins);
+    let mut content_type = None;
+    let mut body = Vec::new();
+    let mut pushes = 0;
+
+    while let Some(tag) = fields.next() {
+        match tag {
+            Instruction::Op(OP_ENDIF) => break,
+            // An empty push (OP_0) separates the header fields from the body.
+            Instruction::Push(&[]) => {
+                for instruction in fields.by_ref() {
+                    match instruction {
+                        Instruction::Push(data) => {
+                            body.extend_from_slice(data);
+                            pushes += 1;
+                        }
+                        Instruction::Op(OP_ENDIF) => break,
+                        Instruction::Op(_) => return None,
+                    }
+                }
+                break;
+            }
+            _ => {
+                let Some(Instruction::Push(value)) = fields.next() else {
+                    return None;
+                };
+                if matches!(
+                    tag,
+                    Instruction::Push(CONTENT_TYPE_TAG) | Instruction::Op(OP_1)
+                ) {
+                    content_type = Some(String::from_utf8_lossy(value).into_owned());
+                }
+            }
+        }
+    }
+
+    let end = instructions.len() - fields.len();
+    let mut witness_items: Vec<usize> = instructions[start..end]
+        .iter()
+        .map(|&(item, _)| item)
+        .collect();
+    witness_items.dedup();
+    Some(Inscription {
+        content_type,
+        body,
+        witness_items,
+        pushes,
+    })
+}
@@ -0,0 +1,40 @@
+use std::process;
+
+use tokio::sync::watch;
+
+/// Exit status of a scan stopped with Ctrl-C, like a shell reports SIGINT.
+pub const EXIT_INTERRUPTED: u8 = 130;
+
+/// Tracks whether Ctrl-C was pressed, so long scans can stop between blocks
+/// and still finish their output.
+pub struct Interrupt(watch::Receiver<bool>);
+
+impl Interrupt {
+    /// Replaces the default Ctrl-C behaviour. A second Ctrl-C exits at once.
+    pub fn install() -> Interrupt {
+        let (sender, receiver) = watch::channel(false);
+        tokio::spawn(async move {
+            if tokio::signal::ctrl_c().await.is_err() {
+                return;
+            }
+            eprintln!("Interrupted, saving results. Press Ctrl-C again to quit immediately.");
+            let _ = sender.send(true);
+            if tokio::signal::ctrl_c().await.is_ok() {
+                process::exit(EXIT_INTERRUPTED as i32);
+            }
+        });
+        Interrupt(receiver)
+    }
+
+    pub fn is_set(&self) -> bool {
+        *self.0.borrow()
+    }
+
+    /// Resolves once Ctrl-C has been pressed.
+    pub async fn wait(&mut self) {
+        if self.0.wait_for(|&set| set).await.is_err() {
+            // The handler could not be installed, so this never happens.
+            std::future::pending::<()>().await;
+        }
+    }
+}
@@ -0,0 +1,94 @@
+use std::fmt;
+use std::sync::LazyLock;
+
+use regex::Regex;
+use schemars::JsonSchema;
+use serde::Serialize;
+
+/// Content a message is recognized as, beyond being text.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum MessageKind {
+    /// An `http://` or `https://` URL.
+    Url,
+    Email,
+    /// Text hiding zero-width or other invisible characters, see
+    /// [`crate::detect_steganography`].
+    Steganography,
+    /// Many near-identical data outputs, which may together form an image.
+    Artwork,
+    /// 32 bytes that look like a txid, block hash or merkle root rather than
+    /// text, see [`looks_like_hash`].
+    HashReference,
+}
+
+impl MessageKind {
+    pub fn parse(name: &str) -> Option<MessageKind> {
+        match name {
+            "url" => Some(MessageKind::Url),
+            "email" => Some(MessageKind::Email),
+            "steganography" => Some(MessageKind::Steganography),
+            "artwork" => Some(MessageKind::Artwork),
+            "hash_reference" => Some(MessageKind::HashReference),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for MessageKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            MessageKind::Url => "url",
+            MessageKind::Email => "email",
+            MessageKind::Steganography => "steganography",
+            MessageKind::Artwork => "artwork",
+            MessageKind::HashReference => "hash_reference",
+        })
+    }
+}
+
+static URL: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"(?i)\bhttps?://[^\s<>"'`]+"#).expect("valid URL pattern"));
+
+static EMAIL: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"\b[A-Za-z0-9._%+-]+@[A-Za-z0-9-]+(\.[A-Za-z0-9-]+)*\.[A-Za-z]{2,}\b")
+        .expect("valid email pattern")
+});
+
+/// Fewest distinct values among the 32 bytes of a [`looks_like_hash`] value.
+/// Random bytes have about 30, text and padding far fewer.
+const HASH_MIN_DISTINCT_BYTES: usize = 24;
+
+/// Whether `bytes` could be a SHA-256 hash, such as a txid or merkle root
+/// embedded as a reference: 32 bytes, nearly all distinct and not all
+/// printable. This is a heuristic; any random 32 bytes pass.
+pub fn looks_like_hash(bytes: &[u8]) -> bool {
+    if bytes.len() != 32 || bytes.iter().all(|&b| b.is_ascii_graphic() || b == b' ') {
+        return false;
+    }
+    let mut seen = [false; 256];
+    for &b in bytes {
+        seen[b as usize] = true;
+    }
+    seen.iter().filter(|&&seen| seen).count() >= HASH_MIN_DISTINCT_BYTES
+}
+
+/// Labels a message of 64 hex digits encoding a [`looks_like_hash`] value as
+/// a [`MessageKind::HashReference`]. Failing that, finds the first URL in
+/// `message`, or else the first email address, and returns its kind together
+/// with the matched part of `message`. Punctuation ending a sentence is not
+/// part of a URL.
+pub fn classify_message(message: &str) -> Option<(MessageKind, &str)> {
+    if message.len() == 64 && hex::decode(message).is_ok_and(|bytes| looks_like_hash(&bytes)) {
+        return Some((MessageKind::HashReference, message));
+    }
+    if let Some(url) = URL.find(message) {
+        let url = url
+            .as_str()
+            .trim_end_matches(['.', ',', ';', ':', '!', '?', ')']);
+        return Some((MessageKind::Url, url));
+    }
+    EMAIL
+        .find(message)
+        .map(|email| (MessageKind::Email, email.as_str()))
+}
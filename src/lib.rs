@@ -0,0 +1,983 @@
+use std::collections::HashMap;
+use std::ops::Range;
+
+use base64::prelude::*;
+use serde::{Deserialize, Serialize};
+
+pub mod address;
+pub mod cache;
+pub mod compress;
+pub mod decoder;
+pub mod error;
+pub mod famous;
+pub mod filetype;
+pub mod filter;
+pub mod finding;
+pub mod hexdump;
+pub mod inscription;
+pub mod kind;
+pub mod pool;
+pub mod protocol;
+pub mod ratelimit;
+pub mod raw;
+pub mod score;
+pub mod script;
+pub mod signature;
+pub mod source;
+pub mod stego;
+pub mod stream;
+
+pub use address::script_address;
+pub use cache::{Cache, CacheWriter};
+pub use decoder::{
+    caesar_brute_force, caesar_shift, xor_brute_force, Decoder, DecoderRegistry, Utf16Le, XorKey,
+    CAESAR_MIN_ENGLISH, XOR_MIN_ENGLISH,
+};
+pub use error::{AppError, FetchError};
+pub use famous::famous_message;
+pub use filetype::detect_file_type;
+pub use filter::FindingFilter;
+pub use finding::{Context, Finding, InscriptionSpan, MessageLocation};
+pub use hexdump::{hexdump, HEXDUMP_MAX_BYTES};
+pub use inscription::{extract_inscription, Inscription};
+pub use kind::{classify_message, looks_like_hash, MessageKind};
+pub use pool::{identify_block_pool, identify_coinbase_pool, identify_pool};
+pub use protocol::identify_protocol;
+pub use ratelimit::RateLimiter;
+pub use raw::{parse_block_hex, parse_raw_block, parse_raw_block_hex, parse_raw_transaction_hex};
+pub use score::{english_score, shannon_entropy};
+pub use signature::{parse_signed_message, SignatureCheck, SignedMessage};
+pub use source::{
+    build_client, fetch_block_by_hash, fetch_block_data, fetch_block_sizes, fetch_block_stream,
+    first_block_since, get_with_retry, load_block_file, open_block_file, parse_json_response,
+    search_block_time, Backend, BlockId, BlockSize, BlockSource, BlockchainInfoSource,
+    ClientOptions, HttpClient, MempoolSource, Network, RawBlockSource, ResponseBody, Tip,
+    API_KEY_HEADER, DEFAULT_TIMEOUT,
+};
+pub use stego::{detect_steganography, strip_invisible, zero_width_bits};
+pub use stream::{BlockInfo, BlockStream, BlockVisitor};
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct ScriptPubKey {
+    #[serde(default)]
+    pub asm: Option<String>,
+    #[serde(default)]
+    pub hex: Option<String>,
+    #[serde(rename = "type", default)]
+    pub script_type: Option<String>,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct Vout {
+    #[serde(default)]
+    pub value: Option<f64>,
+    #[serde(default)]
+    pub n: Option<u32>,
+    #[serde(default)]
+    pub script_pub_key: Option<ScriptPubKey>,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct ScriptSig {
+    #[serde(default)]
+    pub asm: Option<String>,
+    #[serde(default)]
+    pub hex: Option<String>,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct Vin {
+    #[serde(default)]
+    pub coinbase: Option<String>,
+    #[serde(default)]
+    pub txid: Option<String>,
+    #[serde(default)]
+    pub vout: Option<u32>,
+    #[serde(default)]
+    pub script_sig: Option<ScriptSig>,
+    #[serde(default)]
+    pub sequence: Option<u64>,
+    #[serde(default)]
+    pub txinwitness: Vec<String>,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct Transaction {
+    pub hash: String,
+    #[serde(default)]
+    pub hex: Option<String>,
+    #[serde(default)]
+    pub vin: Vec<Vin>,
+    #[serde(default)]
+    pub vout: Vec<Vout>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct Block {
+    #[serde(default)]
+    pub height: Option<u64>,
+    pub tx: Vec<Transaction>,
+}
+
+#[derive(Deserialize)]
+pub struct ApiResponse {
+    pub blocks: Vec<Block>,
+}
+
+pub fn is_printable_ascii(s: &str) -> bool {
+    s.chars().all(|c| c.is_ascii() && !c.is_ascii_control())
+}
+
+pub fn extract_hidden_message(hex_data: &str) -> Option<String> {
+    let data = hex::decode(hex_data).ok()?;
+    message_from_bytes(&data)
+}
+
+/// Returns the bytes as a message if they are entirely printable ASCII.
+pub fn message_from_bytes(data: &[u8]) -> Option<String> {
+    // Checking the bytes first avoids building a string for binary data.
+    if data.iter().all(|&b| is_printable_byte(b)) {
+        Some(String::from_utf8_lossy(data).into_owned())
+    } else {
+        None
+    }
+}
+
+/// Minimum length of a printable run reported by [`extract_printable_runs`].
+pub const MIN_RUN_LENGTH: usize = 4;
+
+fn is_printable_byte(b: u8) -> bool {
+    b.is_ascii() && !b.is_ascii_control()
+}
+
+/// Returns every maximal run of printable ASCII in the decoded bytes that is at
+/// least `min_len` characters long, like `strings(1)`.
+pub fn extract_printable_runs(hex_data: &str, min_len: usize) -> Vec<String> {
+    match hex::decode(hex_data) {
+        Ok(data) => printable_runs(&data, min_len),
+        Err(_) => Vec::new(),
+    }
+}
+
+/// Byte-level counterpart of [`extract_printable_runs`].
+pub fn printable_runs(data: &[u8], min_len: usize) -> Vec<String> {
+    Encoding::Ascii.runs(data, min_len)
+}
+
+/// Returns the byte ranges of every maximal run of printable ASCII in `data`
+/// that is at least `min_len` bytes long, without allocating for the text.
+pub fn scan_bytes(data: &[u8], min_len: usize) -> Vec<Range<usize>> {
+    Encoding::Ascii.run_ranges(data, min_len)
+}
+
+/// How decoded bytes are turned into messages.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ExtractMode {
+    /// The whole field must be printable ASCII.
+    #[default]
+    Whole,
+    /// Every printable run of at least [`MIN_RUN_LENGTH`] characters is reported.
+    Runs,
+}
+
+/// Which bytes count as printable text, and how they are decoded.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Encoding {
+    /// Printable 7-bit ASCII only.
+    #[default]
+    Ascii,
+    /// ISO-8859-1: printable ASCII plus the printable high range `0xA0..=0xFF`.
+    Latin1,
+    /// Strict UTF-8 without control characters, for emoji and non-Latin scripts.
+    Utf8,
+}
+
+impl Encoding {
+    pub fn parse(name: &str) -> Option<Encoding> {
+        match name.to_ascii_lowercase().as_str() {
+            "ascii" => Some(Encoding::Ascii),
+            "latin1" | "latin-1" | "iso-8859-1" => Some(Encoding::Latin1),
+            "utf8" | "utf-8" => Some(Encoding::Utf8),
+            _ => None,
+        }
+    }
+
+    fn is_printable(self, b: u8) -> bool {
+        match self {
+            Encoding::Ascii | Encoding::Utf8 => is_printable_byte(b),
+            Encoding::Latin1 => is_printable_byte(b) || b >= 0xa0,
+        }
+    }
+
+    /// Inverse of [`Encoding::decode`] for printable text.
+    fn encode(self, message: &str) -> Vec<u8> {
+        match self {
+            Encoding::Ascii | Encoding::Utf8 => message.as_bytes().to_vec(),
+            Encoding::Latin1 => message.chars().map(|c| c as u8).collect(),
+        }
+    }
+
+    fn decode(self, data: &[u8]) -> String {
+        match self {
+            Encoding::Ascii | Encoding::Utf8 => String::from_utf8_lossy(data).into_owned(),
+            // Latin-1 bytes map one to one onto the first 256 code points.
+            Encoding::Latin1 => data.iter().map(|&b| b as char).collect(),
+        }
+    }
+
+    /// Decodes `data` if all of it is printable text.
+    fn whole(self, data: &[u8]) -> Option<String> {
+        match self {
+            Encoding::Utf8 => std::str::from_utf8(data)
+                .ok()
+                .filter(|text| !text.chars().any(char::is_control))
+                .map(str::to_string),
+            _ => data
+                .iter()
+                .all(|&b| self.is_printable(b))
+                .then(|| self.decode(data)),
+        }
+    }
+
+    /// Returns every maximal run of printable text at least `min_len`
+    /// characters long.
+    fn runs(self, data: &[u8], min_len: usize) -> Vec<String> {
+        if self == Encoding::Utf8 {
+            // Invalid sequences and control characters both end a run.
+            return data
+                .utf8_chunks()
+                .flat_map(|chunk| chunk.valid().split(char::is_control))
+                .filter(|run| !run.is_empty() && run.chars().count() >= min_len)
+                .map(str::to_string)
+                .collect();
+        }
+        self.run_ranges(data, min_len)
+            .into_iter()
+            .map(|range| self.decode(&data[range]))
+            .collect()
+    }
+
+    /// Byte ranges of the printable runs of a single-byte encoding.
+    fn run_ranges(self, data: &[u8], min_len: usize) -> Vec<Range<usize>> {
+        let mut ranges = Vec::new();
+        let mut start = None;
+        for (i, &b) in data.iter().enumerate() {
+            match (self.is_printable(b), start) {
+                (true, None) => start = Some(i),
+                (false, Some(run_start)) => {
+                    if i - run_start >= min_len.max(1) {
+                        ranges.push(run_start..i);
+                    }
+                    start = None;
+                }
+                _ => {}
+            }
+        }
+        if let Some(run_start) = start {
+            if data.len() - run_start >= min_len.max(1) {
+                ranges.push(run_start..data.len());
+            }
+        }
+        ranges
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct ScanOptions {
+    pub mode: ExtractMode,
+    pub encoding: Encoding,
+    /// Messages shorter than this many characters are discarded. Defaults to 1
+    /// in [`ExtractMode::Whole`] and [`MIN_RUN_LENGTH`] in [`ExtractMode::Runs`].
+    pub min_length: Option<usize>,
+    /// Also report the decoded form of messages that look like base64.
+    pub decode_base64: bool,
+    /// Also report messages that are themselves hex-encoded text, up to
+    /// [`MAX_HEX_DEPTH`] levels deep.
+    pub recursive_hex: bool,
+    /// Annotate well-known messages, see [`famous_message`].
+    pub famous: bool,
+    /// Also report the concatenation of consecutive OP_RETURN outputs.
+    pub reassemble: bool,
+    /// In [`ExtractMode::Runs`], skip payloads where less than this fraction
+    /// of the bytes belongs to a reported run.
+    pub min_printable_ratio: Option<f64>,
+    /// Record the offset and this many surrounding bytes of each message.
+    pub context: Option<usize>,
+    /// Verify messages that carry a Bitcoin message signature, see
+    /// [`parse_signed_message`].
+    pub verify_signatures: bool,
+    /// Attach a [`hexdump`] of the payload to findings for embedded files.
+    pub hexdump: bool,
+    /// Custom decoders every payload is run through.
+    pub decoders: DecoderRegistry,
+    /// Also try every single-byte XOR key on payloads, see [`xor_brute_force`].
+    pub xor_brute: bool,
+    /// Also report messages that decode to English with a Caesar shift, see
+    /// [`caesar_brute_force`].
+    pub caesar: bool,
+    /// Only inspect these parts of a transaction, or all of them if `None`.
+    pub locations: Option<Vec<MessageLocation>>,
+    /// Record the address of the output script of findings in outputs, see
+    /// [`script_address`].
+    pub addresses: bool,
+    /// Report the addresses of outputs that contain this text, such as the
+    /// `Love` of `1LoveBPzzD72...`. Matched case-sensitively.
+    pub vanity: Option<String>,
+    /// The network addresses are encoded for.
+    pub network: Network,
+    pub filter: FindingFilter,
+}
+
+impl ScanOptions {
+    /// Whether messages at `location` are looked for.
+    pub fn scans(&self, location: MessageLocation) -> bool {
+        self.locations
+            .as_ref()
+            .is_none_or(|locations| locations.contains(&location))
+    }
+}
+
+fn looks_like_base64(s: &str) -> bool {
+    let data = s.trim_end_matches('=');
+    s.len().is_multiple_of(4)
+        && s.len() - data.len() <= 2
+        && !data.is_empty()
+        && data
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || b == b'+' || b == b'/')
+}
+
+/// Decodes a base64 message, returning the result only if it is itself
+/// printable text.
+pub fn decode_base64_message(s: &str) -> Option<String> {
+    if !looks_like_base64(s) {
+        return None;
+    }
+    let decoded = BASE64_STANDARD.decode(s).ok()?;
+    message_from_bytes(&decoded).filter(|message| !message.is_empty())
+}
+
+/// How many nested layers of hex encoding [`ScanOptions::recursive_hex`] peels off.
+pub const MAX_HEX_DEPTH: usize = 3;
+
+/// Decodes a message that is itself a hex string, returning the result only if
+/// it is printable text.
+pub fn decode_hex_message(s: &str) -> Option<String> {
+    if s.is_empty() || !s.len().is_multiple_of(2) || !s.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return None;
+    }
+    extract_hidden_message(s).filter(|message| !message.is_empty())
+}
+
+/// Messages extracted from one payload.
+#[derive(Default)]
+struct Extracted {
+    messages: Vec<String>,
+    /// Fraction of the payload covered by the runs, in [`ExtractMode::Runs`].
+    printable_ratio: Option<f64>,
+}
+
+impl From<Vec<String>> for Extracted {
+    fn from(messages: Vec<String>) -> Self {
+        Extracted {
+            messages,
+            printable_ratio: None,
+        }
+    }
+}
+
+fn extract_messages(data: &[u8], options: &ScanOptions) -> Extracted {
+    match options.mode {
+        ExtractMode::Whole => {
+            let min_length = options.min_length.unwrap_or(1);
+            options
+                .encoding
+                .whole(data)
+                .filter(|message| message.chars().count() >= min_length)
+                .into_iter()
+                .collect::<Vec<_>>()
+                .into()
+        }
+        ExtractMode::Runs => {
+            let encoding = options.encoding;
+            let mut messages = encoding.runs(data, options.min_length.unwrap_or(MIN_RUN_LENGTH));
+            let run_bytes: usize = messages
+                .iter()
+                .map(|run| match encoding {
+                    // Decoded Latin-1 takes two bytes per high character.
+                    Encoding::Latin1 => run.chars().count(),
+                    _ => run.len(),
+                })
+                .sum();
+            let ratio = if data.is_empty() {
+                0.0
+            } else {
+                run_bytes as f64 / data.len() as f64
+            };
+            if options.min_printable_ratio.is_some_and(|min| ratio < min) {
+                messages.clear();
+            }
+            Extracted {
+                messages,
+                printable_ratio: Some(ratio),
+            }
+        }
+    }
+}
+
+/// Decodes `hex_data` into `buffer`, reusing its allocation across fields.
+/// Invalid hex decodes to no bytes.
+fn decode_hex_into<'b>(hex_data: &str, buffer: &'b mut Vec<u8>) -> &'b [u8] {
+    buffer.clear();
+    buffer.resize(hex_data.len() / 2, 0);
+    if hex::decode_to_slice(hex_data, buffer).is_err() {
+        buffer.clear();
+    }
+    buffer
+}
+
+/// The transaction field a batch of messages was extracted from.
+struct Field<'a> {
+    tx: &'a Transaction,
+    location: MessageLocation,
+    index: usize,
+    item_index: Option<usize>,
+    raw_hex: &'a str,
+}
+
+impl<'a> Field<'a> {
+    fn new(tx: &'a Transaction, location: MessageLocation, index: usize, raw_hex: &'a str) -> Self {
+        Field {
+            tx,
+            location,
+            index,
+            item_index: None,
+            raw_hex,
+        }
+    }
+
+    fn item(mut self, item_index: usize) -> Self {
+        self.item_index = Some(item_index);
+        self
+    }
+
+    fn finding(&self, message: String, decoder: Option<&str>) -> Finding {
+        let (pool, timestamp) = match self.location {
+            MessageLocation::Coinbase | MessageLocation::CoinbaseTag => {
+                (identify_pool(&message), coinbase_datetime(self.raw_hex))
+            }
+            _ => (None, None),
+        };
+        let (kind, kind_match) = match detect_steganography(&message) {
+            Some(hidden) => (Some(MessageKind::Steganography), Some(hidden)),
+            None => match classify_message(&message) {
+                Some((kind, matched)) => (Some(kind), Some(matched.to_string())),
+                None => (None, None),
+            },
+        };
+        Finding {
+            tx_hash: self.tx.hash.clone(),
+            location: self.location,
+            vin_or_vout_index: self.index,
+            item_index: self.item_index,
+            raw_hex: self.raw_hex.to_string(),
+            decoder: decoder.map(str::to_string),
+            entropy: shannon_entropy(&message),
+            // Scored as it reads, invisible characters would count against it.
+            english_score: english_score(&strip_invisible(&message)),
+            pool,
+            timestamp,
+            file_type: None,
+            payload_length: None,
+            data_outputs: None,
+            significance: None,
+            protocol: None,
+            printable_ratio: None,
+            context: None,
+            signature: None,
+            kind,
+            kind_match,
+            hexdump: None,
+            address: None,
+            inscription_span: None,
+            source_file: None,
+            payload: None,
+            message,
+        }
+    }
+
+    /// A finding for an embedded file, which is reported even though its bytes
+    /// are not printable. It has no message, only the type and length.
+    fn file_finding(&self, file_type: &str, data: &[u8]) -> Finding {
+        let mut finding = self.finding(String::new(), None);
+        finding.file_type = Some(file_type.to_string());
+        finding.payload_length = Some(data.len());
+        finding.payload = Some(data.to_vec());
+        finding
+    }
+
+    /// A finding for meta-protocol data, whose bytes are not a message. Like
+    /// a file finding, it has only the protocol and length.
+    fn protocol_finding(&self, protocol: &str, data: &[u8]) -> Finding {
+        let mut finding = self.finding(String::new(), None);
+        finding.protocol = Some(protocol.to_string());
+        finding.payload_length = Some(data.len());
+        finding.payload = Some(data.to_vec());
+        finding
+    }
+}
+
+/// Looks at a payload as a whole: reports what the custom decoders recover
+/// from it, and embedded files.
+fn push_payload(
+    findings: &mut Vec<Finding>,
+    field: &Field<'_>,
+    data: &[u8],
+    options: &ScanOptions,
+) {
+    let min_length = options.min_length.unwrap_or(MIN_RUN_LENGTH);
+    for decoder in options.decoders.iter() {
+        if let Some(message) = decoder
+            .try_decode(data)
+            .filter(|message| message.chars().count() >= min_length)
+        {
+            findings.push(field.finding(message, Some(decoder.name())));
+        }
+    }
+    if options.xor_brute {
+        if let Some((key, message)) =
+            xor_brute_force(data).filter(|(_, message)| message.chars().count() >= min_length)
+        {
+            findings.push(field.finding(message, Some(&format!("xor {:02x}", key))));
+        }
+    }
+
+    let Some(file_type) = detect_file_type(data) else {
+        return;
+    };
+    let mut finding = field.file_finding(file_type, data);
+    if options.hexdump {
+        finding.hexdump = Some(hexdump(data));
+    }
+    findings.push(finding);
+
+    // Compressed payloads are scanned again after inflating them.
+    if let Some(decompressed) = compress::decompress(data, file_type) {
+        push_decoded(
+            findings,
+            field,
+            extract_messages(&decompressed, options),
+            file_type,
+        );
+    }
+}
+
+/// Locates an undecoded message in the raw bytes of its field. Messages
+/// joined from several pushes are not contiguous there and get no context.
+fn message_context(finding: &Finding, encoding: Encoding, bytes: usize) -> Option<Context> {
+    if finding.decoder.is_some() || finding.message.is_empty() {
+        return None;
+    }
+    let raw = hex::decode(&finding.raw_hex).ok()?;
+    let message = encoding.encode(&finding.message);
+    let offset = raw
+        .windows(message.len())
+        .position(|window| window == message)?;
+    let end = offset + message.len();
+    Some(Context {
+        offset,
+        before: hex::encode(&raw[offset.saturating_sub(bytes)..offset]),
+        after: hex::encode(&raw[end..(end + bytes).min(raw.len())]),
+    })
+}
+
+/// Formats the timestamp found in a coinbase, see [`script::coinbase_timestamp`].
+fn coinbase_datetime(coinbase_hex: &str) -> Option<String> {
+    let bytes = hex::decode(coinbase_hex).ok()?;
+    let timestamp = script::coinbase_timestamp(&bytes)?;
+    let datetime = chrono::DateTime::from_timestamp(timestamp as i64, 0)?;
+    Some(datetime.to_rfc3339_opts(chrono::SecondsFormat::Secs, true))
+}
+
+fn push_findings(
+    findings: &mut Vec<Finding>,
+    field: &Field<'_>,
+    found: Extracted,
+    options: &ScanOptions,
+) {
+    let finding = |message, decoder: Option<&str>| {
+        let mut finding = field.finding(message, decoder);
+        finding.printable_ratio = found.printable_ratio;
+        finding
+    };
+    for message in found.messages.iter().cloned() {
+        let decoded = options
+            .decode_base64
+            .then(|| decode_base64_message(&message))
+            .flatten();
+        let mut inner = options
+            .recursive_hex
+            .then(|| decode_hex_message(&message))
+            .flatten();
+        let shifted = options
+            .caesar
+            .then(|| caesar_brute_force(&message))
+            .flatten();
+        findings.push(finding(message, None));
+        if let Some(decoded) = decoded {
+            findings.push(finding(decoded, Some("base64")));
+        }
+        if let Some((shift, decoded)) = shifted {
+            let decoder = match shift {
+                13 => "rot13".to_string(),
+                _ => format!("caesar {}", shift),
+            };
+            findings.push(finding(decoded, Some(&decoder)));
+        }
+        for _ in 0..MAX_HEX_DEPTH {
+            let Some(message) = inner else {
+                break;
+            };
+            inner = decode_hex_message(&message);
+            findings.push(finding(message, Some("hex")));
+        }
+    }
+}
+
+/// Pushes messages recovered by transforming a payload, labelled with `decoder`.
+fn push_decoded(findings: &mut Vec<Finding>, field: &Field<'_>, found: Extracted, decoder: &str) {
+    for message in found.messages {
+        let mut finding = field.finding(message, Some(decoder));
+        finding.printable_ratio = found.printable_ratio;
+        findings.push(finding);
+    }
+}
+
+/// Reports the combined payload of every run of two or more consecutive
+/// OP_RETURN outputs, for messages split across outputs.
+fn push_reassembled(findings: &mut Vec<Finding>, tx: &Transaction, options: &ScanOptions) {
+    let mut runs: Vec<(usize, Vec<Vec<u8>>)> = Vec::new();
+    let mut previous = None;
+
+    for (index, vout) in tx.vout.iter().enumerate() {
+        let Some(script_pub_key) = &vout.script_pub_key else {
+            continue;
+        };
+        if script_pub_key.script_type.as_deref() != Some("nulldata") {
+            continue;
+        }
+        let Some(script) = script_pub_key
+            .hex
+            .as_deref()
+            .and_then(|h| hex::decode(h).ok())
+        else {
+            continue;
+        };
+        let payload = script::op_return_payload(&script).unwrap_or(script);
+        match runs.last_mut() {
+            Some((_, pieces)) if previous == index.checked_sub(1) => pieces.push(payload),
+            _ => runs.push((index, vec![payload])),
+        }
+        previous = Some(index);
+    }
+
+    for (first_index, pieces) in runs.into_iter().filter(|(_, pieces)| pieces.len() > 1) {
+        let combined = pieces.concat();
+        let raw_hex = hex::encode(&combined);
+        let field = Field::new(tx, MessageLocation::OpReturn, first_index, &raw_hex);
+        push_decoded(
+            findings,
+            &field,
+            extract_messages(&combined, options),
+            "reassembled",
+        );
+    }
+}
+
+/// Number of near-identical data outputs from which a transaction is flagged
+/// as possible embedded artwork.
+pub const ARTWORK_MIN_OUTPUTS: usize = 20;
+
+/// Outputs whose scripts can carry data in place of keys or key hashes.
+const DATA_SCRIPT_TYPES: [&str; 4] = ["pubkey", "pubkeyhash", "multisig", "nonstandard"];
+
+/// Flags a transaction with at least [`ARTWORK_MIN_OUTPUTS`] outputs of the
+/// same script type, script length and value, the shape of images spread
+/// over fake keys. Such outputs carry no readable message, so this is only a
+/// pointer for manual investigation.
+fn push_artwork(findings: &mut Vec<Finding>, tx: &Transaction) {
+    // (script type, script length, value) -> (first output index, count)
+    let mut groups: HashMap<(&str, usize, Option<u64>), (usize, usize)> = HashMap::new();
+    for (index, vout) in tx.vout.iter().enumerate() {
+        let Some(script_pub_key) = &vout.script_pub_key else {
+            continue;
+        };
+        let (Some(script_type), Some(hex)) =
+            (script_pub_key.script_type.as_deref(), &script_pub_key.hex)
+        else {
+            continue;
+        };
+        if !DATA_SCRIPT_TYPES.contains(&script_type) {
+            continue;
+        }
+        let key = (script_type, hex.len(), vout.value.map(f64::to_bits));
+        groups.entry(key).or_insert((index, 0)).1 += 1;
+    }
+
+    let Some(&(first_index, count)) = groups
+        .values()
+        .filter(|(_, count)| *count >= ARTWORK_MIN_OUTPUTS)
+        .max_by_key(|(index, count)| (*count, std::cmp::Reverse(*index)))
+    else {
+        return;
+    };
+    let raw_hex = tx.vout[first_index]
+        .script_pub_key
+        .as_ref()
+        .and_then(|script_pub_key| script_pub_key.hex.as_deref())
+        .unwrap_or_default();
+    let field = Field::new(tx, MessageLocation::ScriptPubKey, first_index, raw_hex);
+    let mut finding = field.finding(String::new(), None);
+    finding.data_outputs = Some(count);
+    finding.kind = Some(MessageKind::Artwork);
+    finding.kind_match = None;
+    findings.push(finding);
+}
+
+pub fn check_transaction_for_messages(tx: &Transaction) -> Vec<Finding> {
+    check_transaction_with_options(tx, &ScanOptions::default())
+}
+
+/// Which input of a transaction is its coinbase.
+#[derive(Clone, Copy)]
+enum CoinbaseInput {
+    /// Whichever input the source marked as one.
+    Marked,
+    /// The first, as the transaction is the first of its block.
+    First,
+    None,
+}
+
+impl CoinbaseInput {
+    fn is_coinbase(self, index: usize, vin: &Vin) -> bool {
+        match self {
+            CoinbaseInput::Marked => vin.coinbase.is_some(),
+            CoinbaseInput::First => index == 0,
+            CoinbaseInput::None => false,
+        }
+    }
+}
+
+/// Scans a transaction on its own, taking inputs the source marked as
+/// coinbase for the coinbase.
+pub fn check_transaction_with_options(tx: &Transaction, options: &ScanOptions) -> Vec<Finding> {
+    scan_transaction(tx, CoinbaseInput::Marked, options)
+}
+
+/// Scans the transaction at `index` of a block. Only the first input of the
+/// first transaction is the coinbase; every other input is a scriptSig, even
+/// one the source marked as coinbase.
+pub fn check_block_transaction(
+    tx: &Transaction,
+    index: usize,
+    options: &ScanOptions,
+) -> Vec<Finding> {
+    let coinbase = if index == 0 {
+        CoinbaseInput::First
+    } else {
+        CoinbaseInput::None
+    };
+    scan_transaction(tx, coinbase, options)
+}
+
+fn scan_transaction(
+    tx: &Transaction,
+    coinbase_input: CoinbaseInput,
+    options: &ScanOptions,
+) -> Vec<Finding> {
+    let mut findings = Vec::new();
+    let mut buffer = Vec::new();
+
+    // Check vin for coinbase, scriptSig and witness data
+    let coinbase =
+        options.scans(MessageLocation::Coinbase) || options.scans(MessageLocation::CoinbaseTag);
+    for (index, vin) in tx.vin.iter().enumerate() {
+        // Sources put the input script in `coinbase` or `script_sig`
+        // depending on what they take the input for.
+        let script_hex = vin
+            .coinbase
+            .as_ref()
+            .or_else(|| vin.script_sig.as_ref().and_then(|s| s.hex.as_ref()));
+        let is_coinbase = coinbase_input.is_coinbase(index, vin);
+
+        if let Some(coinbase) = script_hex.filter(|_| coinbase && is_coinbase) {
+            let bytes = decode_hex_into(coinbase, &mut buffer);
+            let found = extract_messages(bytes, options);
+
+            // Skipping the BIP34 height often leaves a clean miner tag behind.
+            let tag = script::strip_bip34_height(bytes)
+                .map(|(_, rest)| extract_messages(rest, options))
+                .unwrap_or_default();
+            let tag = Extracted {
+                messages: tag
+                    .messages
+                    .into_iter()
+                    .filter(|m| !found.messages.contains(m))
+                    .collect(),
+                ..tag
+            };
+
+            let field = Field::new(tx, MessageLocation::Coinbase, index, coinbase);
+            push_findings(&mut findings, &field, found, options);
+            let field = Field::new(tx, MessageLocation::CoinbaseTag, index, coinbase);
+            push_findings(&mut findings, &field, tag, options);
+        }
+
+        if let Some(hex) =
+            script_hex.filter(|_| !is_coinbase && options.scans(MessageLocation::ScriptSig))
+        {
+            let script = decode_hex_into(hex, &mut buffer);
+            let found = extract_messages(script, options);
+            let field = Field::new(tx, MessageLocation::ScriptSig, index, hex);
+            push_findings(&mut findings, &field, found, options);
+            for instruction in script::instructions(script).unwrap_or_default() {
+                if let script::Instruction::Push(data) = instruction {
+                    push_payload(&mut findings, &field, data, options);
+                }
+            }
+        }
+
+        if options.scans(MessageLocation::Witness) {
+            for (i, item) in vin.txinwitness.iter().enumerate() {
+                let data = decode_hex_into(item, &mut buffer);
+                let found = extract_messages(data, options);
+                let field = Field::new(tx, MessageLocation::Witness, index, item).item(i);
+                push_findings(&mut findings, &field, found, options);
+                push_payload(&mut findings, &field, data, options);
+            }
+        }
+
+        let inscription = options
+            .scans(MessageLocation::Inscription)
+            .then(|| extract_inscription(&vin.txinwitness))
+            .flatten();
+        if let Some(inscription) = inscription {
+            let raw_hex = hex::encode(&inscription.body);
+            let field = Field::new(tx, MessageLocation::Inscription, index, &raw_hex);
+            let span = InscriptionSpan {
+                length: inscription.body.len(),
+                witness_items: inscription.witness_items.clone(),
+                pushes: inscription.pushes,
+            };
+            let first = findings.len();
+            if inscription.is_text() {
+                if let Ok(text) = String::from_utf8(inscription.body) {
+                    push_findings(&mut findings, &field, vec![text].into(), options);
+                }
+            } else {
+                push_payload(&mut findings, &field, &inscription.body, options);
+            }
+            for finding in &mut findings[first..] {
+                finding.inscription_span = Some(span.clone());
+            }
+        }
+    }
+
+    // Check vout for OP_RETURN, scriptPubKey and multisig data
+    for (index, vout) in tx.vout.iter().enumerate() {
+        if let Some(script_pub_key) = &vout.script_pub_key {
+            if let Some(hex) = &script_pub_key.hex {
+                let script = decode_hex_into(hex, &mut buffer);
+                if script.is_empty() {
+                    continue;
+                }
+                if let (Some(pattern), true) =
+                    (&options.vanity, options.scans(MessageLocation::Address))
+                {
+                    if let Some(address) = script_address(script, options.network) {
+                        if address.contains(pattern.as_str()) {
+                            let field = Field::new(tx, MessageLocation::Address, index, hex);
+                            findings.push(field.finding(address, None));
+                        }
+                    }
+                }
+                if script_pub_key.script_type.as_deref() == Some("nulldata") {
+                    if !options.scans(MessageLocation::OpReturn) {
+                        continue;
+                    }
+                    // Decode only the pushed data, not the OP_RETURN and pushdata opcodes.
+                    let payload =
+                        script::op_return_payload(script).unwrap_or_else(|| script.to_vec());
+                    let field = Field::new(tx, MessageLocation::OpReturn, index, hex);
+                    // Meta-protocol payloads are often obfuscated, so they are
+                    // labelled instead of being decoded as text.
+                    if let Some(protocol) = identify_protocol(&payload) {
+                        findings.push(field.protocol_finding(protocol, &payload));
+                        continue;
+                    }
+                    // A bare hash reads as garbage, so it is reported as its hex,
+                    // which classify_message labels a hash reference.
+                    if looks_like_hash(&payload) {
+                        findings.push(field.finding(hex::encode(&payload), None));
+                        continue;
+                    }
+                    let found = extract_messages(&payload, options);
+                    push_findings(&mut findings, &field, found, options);
+                    push_payload(&mut findings, &field, &payload, options);
+                } else {
+                    if options.scans(MessageLocation::ScriptPubKey) {
+                        let found = extract_messages(script, options);
+                        let field = Field::new(tx, MessageLocation::ScriptPubKey, index, hex);
+                        push_findings(&mut findings, &field, found, options);
+                    }
+
+                    let script_type = script_pub_key.script_type.as_deref();
+                    if options.scans(MessageLocation::P2msData)
+                        && matches!(script_type, Some("multisig") | Some("nonstandard"))
+                    {
+                        if let Some(payload) = script::p2ms_payload(script) {
+                            let found = extract_messages(&payload, options);
+                            let field = Field::new(tx, MessageLocation::P2msData, index, hex);
+                            push_findings(&mut findings, &field, found, options);
+                            push_payload(&mut findings, &field, &payload, options);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    if options.reassemble && options.scans(MessageLocation::OpReturn) {
+        push_reassembled(&mut findings, tx, options);
+    }
+    if options.scans(MessageLocation::ScriptPubKey) {
+        push_artwork(&mut findings, tx);
+    }
+
+    findings.retain(|finding| options.scans(finding.location) && options.filter.matches(finding));
+    if let Some(bytes) = options.context {
+        for finding in &mut findings {
+            finding.context = message_context(finding, options.encoding, bytes);
+        }
+    }
+    if options.famous {
+        for finding in &mut findings {
+            finding.significance = famous_message(&finding.message).map(str::to_string);
+        }
+    }
+    if options.verify_signatures {
+        for finding in &mut findings {
+            finding.signature = parse_signed_message(&finding.message).map(|signed| signed.check());
+        }
+    }
+    if options.addresses {
+        for finding in &mut findings {
+            if finding.location.is_output() {
+                finding.address = hex::decode(&finding.raw_hex)
+                    .ok()
+                    .and_then(|script| script_address(&script, options.network));
+            }
+        }
+    }
+    findings
+}
@@ -1,173 +1,984 @@
-use reqwest;
-use serde::{Deserialize, Serialize};
+mod checkpoint;
+mod cli;
+mod dump;
+mod estimate;
+mod interrupt;
+mod output;
+mod profile;
+mod progress;
+mod sqlite;
+mod template;
+mod webhook;
+
+use bitcoin_message_finder::{
+    build_client, check_block_transaction, check_transaction_with_options, fetch_block_sizes,
+    first_block_since, load_block_file, open_block_file, AppError, Backend, Block, BlockId,
+    BlockSource, BlockStream, Cache, ClientOptions, DecoderRegistry, ExtractMode, FetchError,
+    Finding, FindingFilter, HttpClient, MessageLocation, Network, ScanOptions, Tip, Utf16Le,
+};
+use checkpoint::Checkpoint;
+use clap::Parser;
+use cli::{Cli, Command, Select};
+use dump::Dumper;
+use estimate::Estimate;
+use futures::stream::{self, StreamExt};
+use interrupt::{Interrupt, EXIT_INTERRUPTED};
+use output::{Format, Output, OutputOptions};
+use profile::{BlockTiming, Profile};
+
+use sqlite::Database;
 use std::env;
+use std::fs;
 use std::io::{self, Write};
+use std::path::PathBuf;
+use std::process::{self, ExitCode};
+use std::time::{Duration, Instant};
+use tracing_subscriber::EnvFilter;
+use webhook::Webhook;
 
-#[derive(Deserialize, Serialize, Debug)]
-struct ScriptPubKey {
-    #[serde(default)]
-    asm: Option<String>,
-    #[serde(default)]
-    hex: Option<String>,
-    #[serde(rename = "type", default)]
-    script_type: Option<String>,
-}
-
-#[derive(Deserialize, Serialize, Debug)]
-struct Vout {
-    #[serde(default)]
-    value: Option<f64>,
-    #[serde(default)]
-    n: Option<u32>,
-    #[serde(default)]
-    script_pub_key: Option<ScriptPubKey>,
-}
-
-#[derive(Deserialize, Serialize, Debug)]
-struct ScriptSig {
-    #[serde(default)]
-    asm: Option<String>,
-    #[serde(default)]
-    hex: Option<String>,
-}
-
-#[derive(Deserialize, Serialize, Debug)]
-struct Vin {
-    #[serde(default)]
-    coinbase: Option<String>,
-    #[serde(default)]
-    txid: Option<String>,
-    #[serde(default)]
-    vout: Option<u32>,
-    #[serde(default)]
-    script_sig: Option<ScriptSig>,
-    #[serde(default)]
-    sequence: Option<u64>,
-}
-
-#[derive(Deserialize, Serialize, Debug)]
-struct Transaction {
-    hash: String,
-    #[serde(default)]
-    hex: Option<String>,
-    #[serde(default)]
-    vin: Vec<Vin>,
-    #[serde(default)]
-    vout: Vec<Vout>,
-}
-
-#[derive(Deserialize, Debug)]
-struct Block {
-    tx: Vec<Transaction>,
-}
-
-#[derive(Deserialize)]
-struct ApiResponse {
-    blocks: Vec<Block>,
-}
-
-async fn fetch_block_data(block_height: &str) -> Result<Block, Box<dyn std::error::Error>> {
-    let url = format!("https://blockchain.info/block-height/{}?format=json", block_height);
-    let resp = reqwest::get(&url).await?.json::<ApiResponse>().await?;
-    let block = resp.blocks.into_iter().next().ok_or("No blocks found")?;
-    Ok(block)
-}
-
-fn is_printable_ascii(s: &str) -> bool {
-    s.chars().all(|c| c.is_ascii() && !c.is_ascii_control())
-}
-
-fn extract_hidden_message(hex_data: &str) -> Option<String> {
-    let data = hex::decode(hex_data).ok()?;
-    let message = String::from_utf8_lossy(&data);
-    if is_printable_ascii(&message) {
-        Some(message.to_string())
-    } else {
-        None
+struct Args {
+    command: Command,
+    backend: Backend,
+    network: Network,
+    raw: bool,
+    scan: ScanOptions,
+    report: OutputOptions,
+    output: Option<String>,
+    sqlite: Option<String>,
+    webhook: Option<String>,
+    dump_dir: Option<String>,
+    dump_all: bool,
+    client: ClientOptions,
+    verbose: u8,
+    no_cache: bool,
+    refresh: bool,
+    no_color: bool,
+}
+
+fn invalid_value(flag: &str, value: &str) -> AppError {
+    AppError::InvalidInput(format!("Invalid value for {}: '{}'.", flag, value))
+}
+
+/// Reads a default from the environment variable `name`. Unset and empty
+/// variables are ignored, invalid ones are an error.
+fn env_default<T>(name: &str, parse: impl Fn(&str) -> Option<T>) -> Result<Option<T>, AppError> {
+    match env::var(name) {
+        Ok(value) if !value.is_empty() => parse(&value)
+            .map(Some)
+            .ok_or_else(|| invalid_value(name, &value)),
+        _ => Ok(None),
     }
 }
 
-fn check_transaction_for_messages(tx: &Transaction) -> Vec<String> {
-    let mut messages = Vec::new();
+fn parse_args() -> Result<Args, AppError> {
+    let cli = Cli::parse();
+    if let Some(shell) = cli.completions {
+        cli::print_completions(shell);
+        process::exit(0);
+    }
+    if cli.print_schema {
+        println!("{}", serde_json::to_string_pretty(&Finding::schema())?);
+        process::exit(0);
+    }
+    let command = cli.command.ok_or_else(|| AppError::Usage(cli::usage()))?;
+    let options = cli.options;
+
+    // Flags take precedence; the environment only fills in what they left unset.
+    let backend = match options.backend {
+        Some(backend) => backend,
+        None => env_default("BMF_BACKEND", Backend::parse)?.unwrap_or(Backend::BlockchainInfo),
+    };
+    let network = match options.network {
+        Some(network) => network,
+        None => env_default("BMF_NETWORK", Network::parse)?.unwrap_or_default(),
+    };
+    let min_length = match options.min_length {
+        Some(min_length) => Some(min_length),
+        None => env_default("BMF_MIN_LENGTH", |value| value.parse().ok())?,
+    };
+
+    if options.template.is_some() && options.format != Format::Text {
+        return Err(AppError::InvalidInput(
+            "--template only applies to --format text.".to_string(),
+        ));
+    }
 
-    // Check vin for coinbase and scriptSig
-    for vin in &tx.vin {
-        if let Some(coinbase) = &vin.coinbase {
-            if let Some(message) = extract_hidden_message(coinbase) {
-                messages.push(format!("Coinbase: {}", message));
+    let locations = match options.exclude {
+        Some(excluded) => Some(
+            MessageLocation::ALL
+                .into_iter()
+                .filter(|location| !excluded.contains(location))
+                .collect(),
+        ),
+        None => options.only,
+    };
+
+    let mut decoders = DecoderRegistry::new();
+    if options.utf16 {
+        decoders.register(Utf16Le);
+    }
+    for key in options.xor_key {
+        decoders.register(key);
+    }
+
+    let scan = ScanOptions {
+        mode: if options.strings {
+            ExtractMode::Runs
+        } else {
+            ExtractMode::Whole
+        },
+        encoding: options.encoding.unwrap_or_default(),
+        min_length,
+        decode_base64: options.decode_base64,
+        recursive_hex: options.recursive_hex,
+        famous: options.famous,
+        reassemble: options.reassemble,
+        min_printable_ratio: options.min_printable_ratio,
+        context: options.context,
+        verify_signatures: options.verify_signatures,
+        hexdump: options.hexdump,
+        decoders,
+        xor_brute: options.xor_brute,
+        caesar: options.caesar,
+        locations,
+        addresses: options.addresses,
+        vanity: options.vanity,
+        network,
+        filter: FindingFilter {
+            search: options.search,
+            case_sensitive: options.case_sensitive,
+            regex: options.regex,
+            max_entropy: options.max_entropy,
+            min_english: options.min_english,
+            kind: options.kind,
+        },
+    };
+    let report = OutputOptions {
+        format: options.format,
+        show_empty: options.show_empty,
+        unique: options.unique,
+        quiet: options.quiet,
+        summary: options.summary,
+        clusters: options.clusters,
+        first_only: options.first_only,
+        template: options.template,
+        limit_per_tx: options.limit_per_tx.map(|limit| limit as usize),
+        ..OutputOptions::default()
+    };
+    let client = ClientOptions {
+        timeout: Duration::from_secs(options.timeout_secs),
+        user_agent: options.user_agent,
+        api_key: options.api_key,
+        proxy: options.proxy,
+        max_requests_per_sec: options.max_requests_per_sec,
+        max_block_bytes: options.max_block_bytes,
+    };
+
+    Ok(Args {
+        command,
+        backend,
+        network,
+        raw: options.raw,
+        scan,
+        report,
+        output: options.output,
+        sqlite: options.sqlite,
+        webhook: options.webhook,
+        dump_dir: options.dump_dir,
+        dump_all: options.dump_all,
+        client,
+        verbose: options.verbose,
+        no_cache: options.no_cache,
+        refresh: options.refresh,
+        no_color: options.no_color,
+    })
+}
+
+/// How a scan over many blocks or transactions ended.
+#[derive(Default)]
+struct Outcome {
+    failures: usize,
+    /// Ctrl-C stopped the scan early.
+    interrupted: bool,
+    /// Per-block timings of range scans, for `--profile`.
+    timings: Vec<BlockTiming>,
+}
+
+/// Scans the blocks at `heights`, which are in ascending order.
+async fn scan_range(
+    source: &dyn BlockSource,
+    heights: Vec<u64>,
+    concurrency: usize,
+    options: &ScanOptions,
+    output: &mut Output,
+    checkpoint: Option<&Checkpoint>,
+    interrupt: &mut Interrupt,
+) -> Result<Outcome, AppError> {
+    let (start, total) = (heights.first().copied().unwrap_or(0), heights.len());
+    let mut failed = Vec::new();
+    let mut scanned = 0;
+    let mut timings = Vec::new();
+
+    // `buffered` fetches up to `concurrency` blocks at once but yields them in
+    // height order, so output stays deterministic.
+    let mut blocks = stream::iter(heights)
+        .map(|height| async move {
+            let started = Instant::now();
+            let result = source.fetch_stream(BlockId::Height(height)).await;
+            (height, result, started.elapsed())
+        })
+        .buffered(concurrency);
+
+    let bar = progress::bar(total as u64, "blocks");
+    loop {
+        // Dropping the stream on Ctrl-C cancels the fetches in flight.
+        let next = tokio::select! {
+            next = blocks.next() => next,
+            _ = interrupt.wait() => None,
+        };
+        let Some((height, result, fetch)) = next else {
+            break;
+        };
+        let started = Instant::now();
+        // Streamed blocks can still fail to arrive while they are scanned.
+        let scanned_block = result.map_err(AppError::from).and_then(|stream| {
+            bar.suspend(|| scan_block_stream(stream, &BlockId::Height(height), options, output))
+        });
+        match scanned_block {
+            Ok(()) => {}
+            Err(AppError::Fetch(e @ FetchError::TooLarge { .. })) => {
+                bar.suspend(|| eprintln!("Warning: skipping block {}: {}", height, e));
             }
+            Err(AppError::Fetch(e)) => {
+                bar.suspend(|| eprintln!("Error fetching block {}: {}", height, e));
+                failed.push(height);
+            }
+            Err(e) => return Err(e),
+        }
+        // Only a gapless prefix of the range counts as completed.
+        if let (Some(checkpoint), true) = (checkpoint, failed.is_empty()) {
+            output.flush()?;
+            checkpoint.save(height)?;
         }
+        let scan = started.elapsed();
+        bar.inc(1);
+        scanned += 1;
+        tracing::debug!(
+            height,
+            fetch_ms = fetch.as_millis() as u64,
+            scan_ms = scan.as_millis() as u64,
+            percent = scanned * 100 / total,
+            "scanned block"
+        );
+        timings.push(BlockTiming {
+            height,
+            fetch,
+            scan,
+        });
+        if output.done() {
+            break;
+        }
+    }
+    bar.finish_and_clear();
 
-        if let Some(script_sig) = &vin.script_sig {
-            if let Some(hex) = &script_sig.hex {
-                if let Some(message) = extract_hidden_message(hex) {
-                    messages.push(format!("ScriptSig: {}", message));
-                }
+    let interrupted = interrupt.is_set();
+    if interrupted {
+        eprintln!(
+            "Interrupted after {} of {} block(s), starting at height {}.",
+            scanned, total, start
+        );
+    }
+    if !failed.is_empty() {
+        eprintln!("Failed to fetch {} block(s): {:?}", failed.len(), failed);
+    }
+    Ok(Outcome {
+        failures: failed.len(),
+        interrupted,
+        timings,
+    })
+}
+
+/// Scans every txid read from stdin, continuing past failed fetches.
+async fn scan_stdin_txids(
+    source: &dyn BlockSource,
+    options: &ScanOptions,
+    output: &mut Output,
+    interrupt: &mut Interrupt,
+) -> Result<Outcome, AppError> {
+    let mut failed = Vec::new();
+
+    for line in io::stdin().lines() {
+        if interrupt.is_set() || output.done() {
+            break;
+        }
+        let line = line?;
+        let txid = line.trim();
+        if txid.is_empty() {
+            continue;
+        }
+        let result = tokio::select! {
+            result = source.fetch_transaction(txid) => result,
+            _ = interrupt.wait() => break,
+        };
+        match result {
+            Ok(tx) => {
+                let findings = check_transaction_with_options(&tx, options);
+                output.tagged_transaction(txid, findings)?;
             }
+            Err(e) => {
+                eprintln!("Error fetching transaction {}: {}", txid, e);
+                failed.push(txid.to_string());
+            }
+        }
+    }
+
+    if !failed.is_empty() {
+        eprintln!(
+            "Failed to fetch {} transaction(s): {:?}",
+            failed.len(),
+            failed
+        );
+    }
+    Ok(Outcome {
+        failures: failed.len(),
+        interrupted: interrupt.is_set(),
+        ..Outcome::default()
+    })
+}
+
+/// The `*.json` files in `dir`, in file name order.
+fn json_files(dir: &str) -> Result<Vec<PathBuf>, AppError> {
+    let entries = fs::read_dir(dir).map_err(|source| FetchError::File {
+        path: dir.to_string(),
+        source,
+    })?;
+    let mut paths = Vec::new();
+    for entry in entries {
+        let path = entry?.path();
+        if path.is_file() && path.extension().is_some_and(|ext| ext == "json") {
+            paths.push(path);
+        }
+    }
+    paths.sort();
+    Ok(paths)
+}
+
+/// Scans every block file in `dir`, tagging findings with the file name.
+/// Files that cannot be read as blocks are skipped with a warning.
+fn scan_dir(dir: &str, options: &ScanOptions, output: &mut Output) -> Result<(), AppError> {
+    for path in json_files(dir)? {
+        if output.done() {
+            break;
+        }
+        let name = path
+            .file_name()
+            .map_or_else(String::new, |name| name.to_string_lossy().into_owned());
+        output.set_source_file(Some(name.clone()));
+        let scanned = open_block_file(&path.to_string_lossy())
+            .map_err(AppError::from)
+            .and_then(|stream| {
+                output.block_stream(stream, options, |output, info| {
+                    output.block_header(&match info.tx_count {
+                        Some(tx_count) => format!("=== {} ({} transactions) ===", name, tx_count),
+                        None => format!("=== {} ===", name),
+                    })
+                })
+            });
+        match scanned {
+            Err(AppError::Fetch(e)) => eprintln!("Warning: skipping {}: {}", path.display(), e),
+            scanned => scanned?,
         }
     }
+    output.set_source_file(None);
+    Ok(())
+}
+
+async fn scan_block(
+    source: &dyn BlockSource,
+    id: BlockId,
+    options: &ScanOptions,
+    output: &mut Output,
+) -> Result<(), AppError> {
+    let stream = source.fetch_stream(id.clone()).await?;
+    scan_block_stream(stream, &id, options, output)
+}
+
+/// Scans a fetched block under a header naming it.
+fn scan_block_stream(
+    stream: BlockStream,
+    id: &BlockId,
+    options: &ScanOptions,
+    output: &mut Output,
+) -> Result<(), AppError> {
+    output.block_stream(stream, options, |output, info| {
+        let label = info.height.map_or(id.to_string(), |h| h.to_string());
+        output.block_header(&match info.tx_count {
+            Some(tx_count) => format!("=== Block {} ({} transactions) ===", label, tx_count),
+            None => format!("=== Block {} ===", label),
+        })
+    })
+}
+
+/// Polls the chain tip until Ctrl-C, scanning every block that appears. A
+/// tip that changes hash without advancing is a reorg and gets scanned again.
+async fn watch(
+    source: &dyn BlockSource,
+    interval: Duration,
+    options: &ScanOptions,
+    output: &mut Output,
+    interrupt: &mut Interrupt,
+) -> Result<Outcome, AppError> {
+    let mut last: Option<Tip> = None;
 
-    // Check vout for OP_RETURN and scriptPubKey
-    for vout in &tx.vout {
-        if let Some(script_pub_key) = &vout.script_pub_key {
-            if let Some(hex) = &script_pub_key.hex {
-                if script_pub_key.script_type.as_deref() == Some("nulldata") {
-                    if let Some(message) = extract_hidden_message(hex) {
-                        messages.push(format!("OP_RETURN: {}", message));
+    while !interrupt.is_set() {
+        let tip = tokio::select! {
+            tip = source.tip() => tip,
+            _ = interrupt.wait() => break,
+        };
+        match tip {
+            Ok(tip) => {
+                let ids: Vec<BlockId> = match &last {
+                    None => vec![BlockId::Hash(tip.hash.clone())],
+                    Some(prev) if tip.height > prev.height => (prev.height + 1..=tip.height)
+                        .map(BlockId::Height)
+                        .collect(),
+                    Some(prev) if tip.hash != prev.hash => {
+                        output.info(&format!("Reorg at height {}, rescanning.", tip.height));
+                        vec![BlockId::Hash(tip.hash.clone())]
                     }
-                } else {
-                    if let Some(message) = extract_hidden_message(hex) {
-                        messages.push(format!("ScriptPubKey: {}", message));
+                    Some(_) => Vec::new(),
+                };
+                for id in ids {
+                    let result = tokio::select! {
+                        result = scan_block(source, id.clone(), options, output) => result,
+                        _ = interrupt.wait() => break,
+                    };
+                    if let Err(e) = result {
+                        eprintln!("Error scanning block {}: {}", id, e);
+                    }
+                    if output.done() {
+                        return Ok(Outcome::default());
                     }
                 }
+                last = Some(tip);
             }
+            Err(e) => eprintln!("Error fetching chain tip: {}", e),
+        }
+        tokio::select! {
+            _ = tokio::time::sleep(interval) => {}
+            _ = interrupt.wait() => {}
         }
     }
+    Ok(Outcome {
+        interrupted: true,
+        ..Outcome::default()
+    })
+}
 
-    messages
+fn prompt_transaction_number(tx_count: usize, output: &Output) -> Result<usize, AppError> {
+    let prompt = format!("Enter the transaction number (0 to {}): ", tx_count - 1);
+    // Keep stdout clean for machine-readable formats.
+    if output.shows_info() {
+        print!("{}", prompt);
+        io::stdout().flush()?;
+    } else {
+        eprint!("{}", prompt);
+    }
+
+    let mut tx_num = String::new();
+    io::stdin().read_line(&mut tx_num)?;
+    tx_num
+        .trim()
+        .parse()
+        .map_err(|_| AppError::InvalidInput("Invalid transaction number.".to_string()))
 }
 
-#[tokio::main]
-async fn main() {
-    let args: Vec<String> = env::args().collect();
-    if args.len() != 2 {
-        eprintln!("Usage: cargo run <block_height>");
-        return;
-    }
-    let block_height = &args[1];
-
-    match fetch_block_data(block_height).await {
-        Ok(block) => {
-            let tx_count = block.tx.len();
-            println!("Block {} contains {} transactions.", block_height, tx_count);
-
-            print!("Enter the transaction number (0 to {}): ", tx_count - 1);
-            io::stdout().flush().unwrap();
-
-            let mut tx_num = String::new();
-            io::stdin().read_line(&mut tx_num).unwrap();
-            let tx_num: usize = match tx_num.trim().parse() {
-                Ok(num) if num < tx_count => num,
-                _ => {
-                    eprintln!("Invalid transaction number.");
-                    return;
-                }
-            };
+/// Whether a block command scans every transaction rather than one: without
+/// --tx, searching covers the whole block, and there is nobody to answer the
+/// prompt when stdin is piped or output is quiet.
+fn scans_whole_block(args: &Args, select: &Select) -> bool {
+    let searching = args.scan.filter.has_pattern();
+    let interactive = termion::is_tty(&io::stdin()) && !args.report.quiet;
+    select.all || (select.tx.is_none() && (searching || !interactive))
+}
+
+/// Reports the findings of every transaction of a block, parsing them as
+/// they are scanned.
+fn report_whole_block(
+    args: &Args,
+    stream: BlockStream,
+    label: &str,
+    output: &mut Output,
+) -> Result<(), AppError> {
+    output.block_stream(stream, &args.scan, |output, info| {
+        output.info(&match info.tx_count {
+            Some(tx_count) => format!("Block {} contains {} transactions.", label, tx_count),
+            None => format!("Scanning block {}.", label),
+        });
+        Ok(())
+    })
+}
+
+/// Reports the findings of the transaction of a block selected with `--tx`
+/// or the interactive prompt.
+fn report_block(
+    args: &Args,
+    select: &Select,
+    block: &Block,
+    label: &str,
+    output: &mut Output,
+) -> Result<(), AppError> {
+    let tx_count = block.tx.len();
+    output.info(&format!(
+        "Block {} contains {} transactions.",
+        label, tx_count
+    ));
+    if tx_count == 0 {
+        return Ok(());
+    }
+
+    let tx_num = match select.tx {
+        Some(num) => num,
+        None => prompt_transaction_number(tx_count, output)?,
+    };
+    if tx_num >= tx_count {
+        return Err(AppError::InvalidInput(
+            "Invalid transaction number.".to_string(),
+        ));
+    }
 
-            let selected_tx = &block.tx[tx_num];
-            let tx_json = serde_json::to_string_pretty(&selected_tx).unwrap();
-            println!("Transaction details:\n{}", tx_json);
+    let selected_tx = &block.tx[tx_num];
+    if select.dump_tx && output.shows_info() {
+        let tx_json = serde_json::to_string_pretty(&selected_tx)?;
+        println!("Transaction details:\n{}", tx_json);
+    }
 
-            let messages = check_transaction_for_messages(selected_tx);
-            if !messages.is_empty() {
-                println!("Hidden messages found:");
-                for msg in messages {
-                    println!("{}", msg);
+    let findings = check_block_transaction(selected_tx, tx_num, &args.scan);
+    output.transaction(block.height, Some(tx_num), findings)
+}
+
+/// Logs this crate at debug level with `-v`, and everything at trace level
+/// with `-vv`. Without either only warnings are shown.
+fn init_logging(verbose: u8) {
+    let filter = match verbose {
+        0 => EnvFilter::new("warn"),
+        1 => EnvFilter::new("warn,bitcoin_message_finder=debug"),
+        _ => EnvFilter::new("trace"),
+    };
+    tracing_subscriber::fmt()
+        .with_env_filter(filter)
+        .with_writer(io::stderr)
+        .with_ansi(termion::is_tty(&io::stderr()))
+        .init();
+}
+
+fn block_source(args: &Args, client: HttpClient) -> Result<Box<dyn BlockSource>, AppError> {
+    Ok(if args.raw {
+        args.backend.raw_source(client, args.network)
+    } else {
+        // Blocks near the tip can still be reorged away, so watching bypasses the cache.
+        let cache = if args.no_cache || matches!(args.command, Command::Watch { .. }) {
+            None
+        } else {
+            Cache::default_dir().map(|dir| Cache::new(dir, args.refresh))
+        };
+        args.backend.source(client, args.network, cache)
+    })
+}
+
+/// Exit status when messages were found.
+const EXIT_FOUND: u8 = 0;
+/// Exit status when the scan succeeded without finding anything.
+const EXIT_NOT_FOUND: u8 = 1;
+/// Exit status for usage, fetch and parse errors, including partially failed
+/// range and batch scans.
+const EXIT_ERROR: u8 = 2;
+
+fn exit_code(findings: usize, failures: usize) -> ExitCode {
+    ExitCode::from(if failures > 0 {
+        EXIT_ERROR
+    } else if findings > 0 {
+        EXIT_FOUND
+    } else {
+        EXIT_NOT_FOUND
+    })
+}
+
+/// Finishes the output of a multi-block or batch scan and picks the exit
+/// status, [`EXIT_INTERRUPTED`] if Ctrl-C stopped it.
+fn finish(output: Output, outcome: Outcome) -> Result<ExitCode, AppError> {
+    let findings = output.finish()?;
+    if outcome.interrupted {
+        eprintln!("Saved {} finding(s) from the completed part.", findings);
+        return Ok(ExitCode::from(EXIT_INTERRUPTED));
+    }
+    Ok(exit_code(findings, outcome.failures))
+}
+
+fn parse_range(start: &str, end: &str) -> Result<(u64, u64), AppError> {
+    let (start, end) = match (start.parse::<u64>(), end.parse::<u64>()) {
+        (Ok(start), Ok(end)) => (start, end),
+        _ => {
+            return Err(AppError::InvalidInput(
+                "Block range bounds must be numeric heights.".to_string(),
+            ))
+        }
+    };
+    if start > end {
+        return Err(AppError::InvalidInput(format!(
+            "Invalid block range: start {} is greater than end {}.",
+            start, end
+        )));
+    }
+    Ok((start, end))
+}
+
+/// The range of `--since-timestamp`: from the first block mined at or after
+/// `timestamp` to the height `last`, or to the tip without one.
+async fn since_range(
+    source: &dyn BlockSource,
+    client: &HttpClient,
+    network: Network,
+    timestamp: u64,
+    last: Option<&str>,
+) -> Result<(u64, u64), AppError> {
+    let last = last
+        .map(|height| {
+            height.parse::<u64>().map_err(|_| {
+                AppError::InvalidInput("Block range bounds must be numeric heights.".to_string())
+            })
+        })
+        .transpose()?;
+    let tip = source.tip().await?.height;
+    let end = last.unwrap_or(tip);
+    let since = chrono::DateTime::from_timestamp(timestamp as i64, 0)
+        .map_or(timestamp.to_string(), |datetime| datetime.to_rfc3339());
+    let start = first_block_since(client, network, timestamp, end.min(tip))
+        .await?
+        .ok_or_else(|| {
+            AppError::InvalidInput(format!(
+                "No block up to height {} was mined since {}.",
+                end, since
+            ))
+        })?;
+    eprintln!(
+        "Starting at height {}, the first block mined since {}.",
+        start, since
+    );
+    Ok((start, end))
+}
+
+/// `heights` in ascending order, without duplicates.
+fn distinct_heights(heights: &[u64]) -> Vec<u64> {
+    let mut heights = heights.to_vec();
+    heights.sort_unstable();
+    heights.dedup();
+    heights
+}
+
+fn parse_block_id(block_height: &str) -> Result<BlockId, AppError> {
+    BlockId::parse(block_height).ok_or_else(|| {
+        AppError::InvalidInput(format!(
+            "'{}' is neither a block height nor a block hash.",
+            block_height
+        ))
+    })
+}
+
+/// Fails early if `height` is above the chain tip, which the APIs otherwise
+/// report as a missing block. A tip that can't be fetched is only a warning.
+async fn check_tip(source: &dyn BlockSource, height: u64) -> Result<(), AppError> {
+    match source.tip().await {
+        Ok(tip) if height > tip.height => Err(AppError::InvalidInput(format!(
+            "Height {} is beyond the current tip ({}).",
+            height, tip.height
+        ))),
+        Ok(_) => Ok(()),
+        Err(e) => {
+            eprintln!("Warning: cannot check the chain tip: {}", e);
+            Ok(())
+        }
+    }
+}
+
+/// The blocks a `--dry-run` of `command` would fetch, or `None` without
+/// `--dry-run`.
+fn dry_run_blocks(command: &Command) -> Result<Option<Vec<BlockId>>, AppError> {
+    Ok(match command {
+        Command::Block {
+            block, preflight, ..
+        } if preflight.dry_run => Some(vec![parse_block_id(block)?]),
+        Command::Range {
+            since_timestamp: Some(_),
+            preflight,
+            ..
+        } if preflight.dry_run => {
+            return Err(AppError::InvalidInput(
+                "--dry-run needs the range as heights, not --since-timestamp.".to_string(),
+            ))
+        }
+        Command::Range {
+            start: Some(start),
+            end: Some(end),
+            preflight,
+            ..
+        } if preflight.dry_run => {
+            let (start, end) = parse_range(start, end)?;
+            Some((start..=end).map(BlockId::Height).collect())
+        }
+        Command::Heights {
+            heights, preflight, ..
+        } if preflight.dry_run => Some(
+            distinct_heights(heights)
+                .into_iter()
+                .map(BlockId::Height)
+                .collect(),
+        ),
+        _ => None,
+    })
+}
+
+/// Prints the URLs fetching `ids` would request.
+fn dry_run(args: &Args, ids: &[BlockId]) -> Result<(), AppError> {
+    let source = block_source(args, build_client(&args.client)?)?;
+    let mut stdout = io::stdout().lock();
+    for id in ids {
+        for url in source.planned_urls(id)? {
+            writeln!(stdout, "{}", url)?;
+        }
+    }
+    Ok(())
+}
+
+async fn run() -> Result<ExitCode, AppError> {
+    let mut args = parse_args()?;
+    init_logging(args.verbose);
+    if let Some(ids) = dry_run_blocks(&args.command)? {
+        dry_run(&args, &ids)?;
+        return Ok(ExitCode::SUCCESS);
+    }
+    // https://no-color.org: any non-empty NO_COLOR disables colors.
+    let no_color_env = env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty());
+    args.report.color =
+        !args.no_color && !no_color_env && args.output.is_none() && termion::is_tty(&io::stdout());
+    // Range scans show a bar over blocks instead of one per block.
+    args.report.progress = !matches!(
+        args.command,
+        Command::Range { .. } | Command::Heights { .. }
+    ) && progress::enabled();
+    let client = build_client(&args.client)?;
+    let db = args.sqlite.as_deref().map(Database::open).transpose()?;
+    let dumper = args
+        .dump_dir
+        .as_deref()
+        .map(|dir| Dumper::new(dir, args.dump_all))
+        .transpose()?;
+    let (webhook, events) = match &args.webhook {
+        Some(url) => {
+            let (webhook, events) =
+                Webhook::spawn(client.inner().clone(), client.timeout(), url.clone());
+            (Some(webhook), Some(events))
+        }
+        None => (None, None),
+    };
+    let output = Output::new(args.report.clone(), args.output.as_deref())?
+        .with_database(db)
+        .with_webhook(events)
+        .with_dumper(dumper)
+        .with_highlight(&args.scan.filter);
+
+    let code = scan(&args, client, output).await;
+    // The output is gone, so the webhook sees the end of the events.
+    if let Some(webhook) = webhook {
+        webhook.finish().await;
+    }
+    code
+}
+
+/// Runs the scan selected by `args`.
+async fn scan(args: &Args, client: HttpClient, mut output: Output) -> Result<ExitCode, AppError> {
+    match &args.command {
+        Command::Dir { path } => {
+            scan_dir(path, &args.scan, &mut output)?;
+            Ok(exit_code(output.finish()?, 0))
+        }
+        Command::File { path, select } => {
+            if scans_whole_block(args, select) {
+                report_whole_block(args, open_block_file(path)?, path, &mut output)?;
+            } else {
+                let block = load_block_file(path)?;
+                report_block(args, select, &block, path, &mut output)?;
+            }
+            Ok(exit_code(output.finish()?, 0))
+        }
+        Command::Watch { interval_secs } => {
+            if args.report.format == Format::Json {
+                return Err(AppError::InvalidInput(
+                    "watch never finishes, use --format ndjson instead of json.".to_string(),
+                ));
+            }
+            let source = block_source(args, client)?;
+            let mut interrupt = Interrupt::install();
+            let outcome = watch(
+                source.as_ref(),
+                Duration::from_secs(*interval_secs),
+                &args.scan,
+                &mut output,
+                &mut interrupt,
+            )
+            .await?;
+            finish(output, outcome)
+        }
+        Command::Tx { stdin: true, .. } => {
+            let source = block_source(args, client)?;
+            let mut interrupt = Interrupt::install();
+            let outcome =
+                scan_stdin_txids(source.as_ref(), &args.scan, &mut output, &mut interrupt).await?;
+            finish(output, outcome)
+        }
+        Command::Tx { txid, .. } => {
+            let txid = txid
+                .as_deref()
+                .ok_or_else(|| AppError::Usage(cli::usage()))?;
+            let source = block_source(args, client)?;
+            let tx = source.fetch_transaction(txid).await?;
+            output.info(&format!("Transaction {}:", tx.hash));
+            let findings = check_transaction_with_options(&tx, &args.scan);
+            output.transaction(None, None, findings)?;
+            Ok(exit_code(output.finish()?, 0))
+        }
+        Command::Range {
+            start,
+            end,
+            concurrency,
+            checkpoint,
+            since_timestamp,
+            estimate,
+            profile,
+            preflight,
+        } => {
+            let source = block_source(args, client.clone())?;
+            let (mut start, end) = match (since_timestamp, start, end) {
+                // The one height given with --since-timestamp is the end.
+                (Some(timestamp), last, _) => {
+                    let last = last.as_deref();
+                    since_range(source.as_ref(), &client, args.network, *timestamp, last).await?
+                }
+                (None, Some(start), Some(end)) => parse_range(start, end)?,
+                _ => return Err(AppError::Usage(cli::usage())),
+            };
+            let checkpoint = checkpoint.as_deref().map(Checkpoint::new);
+            if let Some(checkpoint) = &checkpoint {
+                match checkpoint.load()? {
+                    Some(done) if done >= end => {
+                        eprintln!(
+                            "Checkpoint {} already covers the range up to height {}.",
+                            checkpoint.path(),
+                            done
+                        );
+                        return finish(output, Outcome::default());
+                    }
+                    Some(done) if done >= start => {
+                        eprintln!(
+                            "Resuming at height {} from checkpoint {}.",
+                            done + 1,
+                            checkpoint.path()
+                        );
+                        start = done + 1;
+                    }
+                    _ => {}
                 }
+            }
+            if !preflight.no_tip_check {
+                check_tip(source.as_ref(), end).await?;
+            }
+            if *estimate {
+                let sizes = fetch_block_sizes(&client, args.network, start..=end).await?;
+                let estimate = Estimate::new(
+                    &sizes,
+                    args.backend,
+                    args.raw,
+                    *concurrency as usize,
+                    args.client.max_requests_per_sec,
+                );
+                print!("{}", estimate);
+                return Ok(ExitCode::SUCCESS);
+            }
+            let mut interrupt = Interrupt::install();
+            let outcome = scan_range(
+                source.as_ref(),
+                (start..=end).collect(),
+                *concurrency as usize,
+                &args.scan,
+                &mut output,
+                checkpoint.as_ref(),
+                &mut interrupt,
+            )
+            .await?;
+            if let Some(slowest) = profile {
+                eprint!("{}", Profile::new(&outcome.timings, *slowest));
+            }
+            finish(output, outcome)
+        }
+        Command::Heights {
+            heights,
+            concurrency,
+            profile,
+            preflight,
+        } => {
+            let source = block_source(args, client)?;
+            let heights = distinct_heights(heights);
+            if let (false, Some(&highest)) = (preflight.no_tip_check, heights.last()) {
+                check_tip(source.as_ref(), highest).await?;
+            }
+            let mut interrupt = Interrupt::install();
+            let outcome = scan_range(
+                source.as_ref(),
+                heights,
+                *concurrency as usize,
+                &args.scan,
+                &mut output,
+                None,
+                &mut interrupt,
+            )
+            .await?;
+            if let Some(slowest) = profile {
+                eprint!("{}", Profile::new(&outcome.timings, *slowest));
+            }
+            finish(output, outcome)
+        }
+        Command::Block {
+            block,
+            select,
+            preflight,
+        } => {
+            let source = block_source(args, client)?;
+            let block_id = parse_block_id(block)?;
+            if let (false, BlockId::Height(height)) = (preflight.no_tip_check, &block_id) {
+                check_tip(source.as_ref(), *height).await?;
+            }
+            if scans_whole_block(args, select) {
+                let stream = source.fetch_stream(block_id).await?;
+                report_whole_block(args, stream, block, &mut output)?;
             } else {
-                println!("No hidden messages found in this transaction.");
+                let fetched = source.fetch(block_id).await?;
+                report_block(args, select, &fetched, block, &mut output)?;
+            }
+            Ok(exit_code(output.finish()?, 0))
+        }
+    }
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    match run().await {
+        Ok(code) => code,
+        Err(e) => {
+            match e {
+                AppError::Usage(usage) => eprintln!("{}", usage),
+                e => eprintln!("Error: {}", e),
             }
+            ExitCode::from(EXIT_ERROR)
         }
-        Err(e) => eprintln!("Error fetching block data: {}", e),
     }
 }
@@ -0,0 +1,829 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::ops::Range;
+
+use bitcoin_message_finder::{
+    check_block_transaction, identify_coinbase_pool, AppError, BlockInfo, BlockStream,
+    BlockVisitor, Finding, FindingFilter, MessageLocation, ScanOptions, Transaction,
+};
+use indicatif::ProgressBar;
+use owo_colors::OwoColorize;
+use serde::Serialize;
+use tokio::sync::mpsc::UnboundedSender;
+
+use crate::dump::Dumper;
+use crate::progress;
+use crate::sqlite::Database;
+use crate::template::Template;
+use crate::webhook::Event;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Format {
+    Text,
+    Json,
+    /// One JSON object per line, written as soon as it is found.
+    Ndjson,
+    Csv,
+}
+
+impl Format {
+    pub fn parse(name: &str) -> Option<Format> {
+        match name {
+            "text" => Some(Format::Text),
+            "json" => Some(Format::Json),
+            "ndjson" => Some(Format::Ndjson),
+            "csv" => Some(Format::Csv),
+            _ => None,
+        }
+    }
+}
+
+enum Sink {
+    Plain(Box<dyn Write>),
+    Csv(Box<csv::Writer<Box<dyn Write>>>),
+}
+
+#[derive(Clone, Debug)]
+pub struct OutputOptions {
+    pub format: Format,
+    /// List transactions without findings in whole-block text output.
+    pub show_empty: bool,
+    /// Suppress messages that were already reported during this run.
+    pub unique: bool,
+    /// Show a progress bar while scanning the transactions of a block.
+    pub progress: bool,
+    /// Print totals at the end of the run.
+    pub summary: bool,
+    /// Highlight findings with ANSI colors in text output.
+    pub color: bool,
+    /// Print nothing but the findings themselves.
+    pub quiet: bool,
+    /// Report at most this many findings per transaction.
+    pub limit_per_tx: Option<usize>,
+    /// Add the scripts shared by several inputs or outputs to the summary,
+    /// and print the summary even without `summary`.
+    pub clusters: bool,
+    /// Report only the first finding of the run, after which [`Output::done`]
+    /// tells scans to stop.
+    pub first_only: bool,
+    /// Write each finding of text output with this template instead.
+    pub template: Option<Template>,
+}
+
+impl Default for OutputOptions {
+    fn default() -> Self {
+        OutputOptions {
+            format: Format::Text,
+            show_empty: false,
+            unique: false,
+            progress: false,
+            summary: false,
+            color: false,
+            quiet: false,
+            limit_per_tx: None,
+            clusters: false,
+            first_only: false,
+            template: None,
+        }
+    }
+}
+
+/// Totals reported with `--summary`.
+#[derive(Debug, Default, Serialize)]
+struct Summary {
+    transactions: usize,
+    findings: usize,
+    by_location: BTreeMap<String, usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    longest_message: Option<String>,
+    /// Filled in from `scripts` by [`Summary::cluster`] at the end of the run.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    clusters: Vec<Cluster>,
+    /// Findings by the raw hex of their script, with `--clusters`.
+    #[serde(skip)]
+    scripts: Option<HashMap<String, Cluster>>,
+    /// Scripts already counted in `scripts`, so that several messages from
+    /// the same script count once.
+    #[serde(skip)]
+    counted: HashSet<(String, String, MessageLocation, usize, Option<usize>)>,
+}
+
+/// Inputs and outputs whose scripts are byte-identical.
+#[derive(Debug, Clone, Serialize)]
+struct Cluster {
+    count: usize,
+    location: MessageLocation,
+    raw_hex: String,
+    /// The first message found in the script.
+    message: String,
+}
+
+impl Summary {
+    fn count(&mut self, findings: &[Finding]) {
+        self.findings += findings.len();
+        for finding in findings {
+            if let Some(scripts) = &mut self.scripts {
+                let position = (
+                    finding.raw_hex.clone(),
+                    finding.tx_hash.clone(),
+                    finding.location,
+                    finding.vin_or_vout_index,
+                    finding.item_index,
+                );
+                if self.counted.insert(position) {
+                    scripts
+                        .entry(finding.raw_hex.clone())
+                        .or_insert_with(|| Cluster {
+                            count: 0,
+                            location: finding.location,
+                            raw_hex: finding.raw_hex.clone(),
+                            message: finding.message.clone(),
+                        })
+                        .count += 1;
+                }
+            }
+            *self
+                .by_location
+                .entry(finding.location.to_string())
+                .or_insert(0) += 1;
+            let longest = self.longest_message.as_deref().unwrap_or("");
+            if finding.message.chars().count() > longest.chars().count() {
+                self.longest_message = Some(finding.message.clone());
+            }
+        }
+    }
+
+    /// Collects the scripts seen more than once, most frequent first.
+    fn cluster(&mut self) {
+        let Some(scripts) = &self.scripts else {
+            return;
+        };
+        let mut clusters: Vec<Cluster> = scripts
+            .values()
+            .filter(|cluster| cluster.count > 1)
+            .cloned()
+            .collect();
+        clusters.sort_by(|a, b| {
+            b.count
+                .cmp(&a.count)
+                .then_with(|| a.raw_hex.cmp(&b.raw_hex))
+        });
+        self.clusters = clusters;
+    }
+}
+
+impl fmt::Display for Summary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Summary:")?;
+        writeln!(f, "  Transactions scanned: {}", self.transactions)?;
+        writeln!(f, "  Findings: {}", self.findings)?;
+        for (location, count) in &self.by_location {
+            writeln!(f, "    {}: {}", location, count)?;
+        }
+        if let Some(message) = &self.longest_message {
+            writeln!(f, "  Longest message: {}", message)?;
+        }
+        if !self.clusters.is_empty() {
+            writeln!(f, "  Duplicate scripts:")?;
+            for cluster in &self.clusters {
+                writeln!(
+                    f,
+                    "    {}x {}: {}",
+                    cluster.count, cluster.location, cluster.message
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Shortest run of plain text highlighted when there is no search pattern.
+const MIN_READABLE_RUN: usize = 4;
+
+/// Byte ranges of runs of letters, digits and common punctuation, the
+/// readable core of messages that are surrounded by binary noise.
+fn readable_ranges(message: &str) -> Vec<Range<usize>> {
+    let is_readable = |c: char| c.is_alphanumeric() || " .,:;!?'\"-/()".contains(c);
+    let mut ranges = Vec::new();
+    let mut start = None;
+    let mut chars = 0;
+    for (i, c) in message.char_indices().chain([(message.len(), '\0')]) {
+        match (is_readable(c) && i < message.len(), start) {
+            (true, None) => {
+                start = Some(i);
+                chars = 1;
+            }
+            (true, Some(_)) => chars += 1,
+            (false, Some(begin)) => {
+                if chars >= MIN_READABLE_RUN {
+                    ranges.push(begin..i);
+                }
+                start = None;
+            }
+            (false, None) => {}
+        }
+    }
+    ranges
+}
+
+/// Formats a finding for text output, colored if `highlight` is set: search
+/// matches in bold yellow, or else the readable parts of the message in green.
+/// A hexdump follows on indented lines.
+fn render(finding: &Finding, highlight: Option<&FindingFilter>) -> String {
+    let mut text = match highlight {
+        Some(filter) => highlighted(finding, filter),
+        None => finding.to_string(),
+    };
+    if let Some(dump) = &finding.hexdump {
+        for line in dump.lines() {
+            text.push_str("\n    ");
+            text.push_str(line);
+        }
+    }
+    text
+}
+
+/// How findings are written in text output.
+struct Style {
+    /// Set when colors are enabled, with the filter whose matches are highlighted.
+    highlight: Option<FindingFilter>,
+    template: Option<Template>,
+}
+
+impl Style {
+    /// The line of a finding in the block at `height` as transaction `index`:
+    /// the `--template` filled in, or else the rendered finding after `prefix`.
+    fn line(
+        &self,
+        prefix: &str,
+        finding: &Finding,
+        height: Option<u64>,
+        index: Option<usize>,
+    ) -> String {
+        match &self.template {
+            Some(template) => template.render(finding, height, index),
+            None => format!("{}{}", prefix, render(finding, self.highlight.as_ref())),
+        }
+    }
+}
+
+fn highlighted(finding: &Finding, filter: &FindingFilter) -> String {
+    let message = &finding.message;
+    let pattern = filter.has_pattern();
+    let ranges = if pattern {
+        filter.match_ranges(message)
+    } else {
+        readable_ranges(message)
+    };
+
+    let mut text = String::new();
+    let mut pos = 0;
+    for range in ranges {
+        if range.start < pos {
+            continue;
+        }
+        text.push_str(&message[pos..range.start]);
+        let part = &message[range.clone()];
+        if pattern {
+            text.push_str(&part.yellow().bold().to_string());
+        } else {
+            text.push_str(&part.green().to_string());
+        }
+        pos = range.end;
+    }
+    text.push_str(&message[pos..]);
+
+    let mut line = format!("{}:", finding.label().cyan());
+    if finding.has_message() {
+        line.push(' ');
+        line.push_str(&text);
+    }
+    let annotations = finding.annotations();
+    if !annotations.is_empty() {
+        line.push_str(&annotations.dimmed().to_string());
+    }
+    line
+}
+
+#[derive(Serialize)]
+struct Footer<'a> {
+    summary: &'a Summary,
+}
+
+/// JSON output with a summary: one document holding both, instead of the
+/// bare array of findings.
+#[derive(Serialize)]
+struct Summarized<'a> {
+    findings: &'a [Finding],
+    summary: &'a Summary,
+}
+
+/// Renders findings as they are produced. Text and CSV are written
+/// immediately, JSON is collected and written as a single document by
+/// [`Output::finish`].
+pub struct Output {
+    options: OutputOptions,
+    to_file: bool,
+    findings: Vec<Finding>,
+    /// How often each message was seen, tracked for `--unique`.
+    seen: HashMap<String, usize>,
+    summary: Summary,
+    db: Option<Database>,
+    style: Style,
+    /// Receives a copy of every reported finding, see [`crate::webhook`].
+    webhook: Option<UnboundedSender<Event>>,
+    dumper: Option<Dumper>,
+    /// Set on every recorded finding, see [`Finding::source_file`].
+    source_file: Option<String>,
+    sink: Sink,
+}
+
+impl Output {
+    /// Creates an output writing to `path`, or to stdout if no path is given.
+    pub fn new(options: OutputOptions, path: Option<&str>) -> Result<Self, AppError> {
+        let writer: Box<dyn Write> = match path {
+            Some(path) => {
+                let file = File::create(path).map_err(|source| AppError::OutputFile {
+                    path: path.to_string(),
+                    source,
+                })?;
+                Box::new(BufWriter::new(file))
+            }
+            None => Box::new(io::stdout()),
+        };
+
+        let sink = match options.format {
+            Format::Csv => {
+                let mut writer = csv::Writer::from_writer(writer);
+                writer
+                    .write_record(["block_height", "tx_index", "tx_hash", "location", "message"])
+                    .map_err(io::Error::from)?;
+                Sink::Csv(Box::new(writer))
+            }
+            _ => Sink::Plain(writer),
+        };
+
+        let style = Style {
+            highlight: None,
+            template: options.template.clone(),
+        };
+        let summary = Summary {
+            scripts: options.clusters.then(HashMap::new),
+            ..Summary::default()
+        };
+        Ok(Output {
+            options,
+            to_file: path.is_some(),
+            findings: Vec::new(),
+            seen: HashMap::new(),
+            summary,
+            db: None,
+            style,
+            webhook: None,
+            dumper: None,
+            source_file: None,
+            sink,
+        })
+    }
+
+    /// Also inserts every reported finding into `db`.
+    pub fn with_database(mut self, db: Option<Database>) -> Self {
+        self.db = db;
+        self
+    }
+
+    /// Also sends every reported finding to `webhook`.
+    pub fn with_webhook(mut self, webhook: Option<UnboundedSender<Event>>) -> Self {
+        self.webhook = webhook;
+        self
+    }
+
+    /// Also saves the payload of every reported finding with `dumper`.
+    pub fn with_dumper(mut self, dumper: Option<Dumper>) -> Self {
+        self.dumper = dumper;
+        self
+    }
+
+    /// Tags the findings reported from now on with the file they are from.
+    pub fn set_source_file(&mut self, name: Option<String>) {
+        self.source_file = name;
+    }
+
+    /// Highlights the matches of `filter` if colors are enabled.
+    pub fn with_highlight(mut self, filter: &FindingFilter) -> Self {
+        self.style.highlight = self.options.color.then(|| filter.clone());
+        self
+    }
+
+    /// Whether informational output may go to stdout without mixing with
+    /// machine-readable results.
+    pub fn shows_info(&self) -> bool {
+        !self.options.quiet && (self.options.format == Format::Text || self.to_file)
+    }
+
+    /// Prints an informational line to stdout if [`Output::shows_info`].
+    pub fn info(&self, line: &str) {
+        if self.shows_info() {
+            println!("{}", line);
+        }
+    }
+
+    /// Writes a per-block header in text output.
+    pub fn block_header(&mut self, line: &str) -> Result<(), AppError> {
+        if let (Format::Text, Sink::Plain(out)) = (self.options.format, &mut self.sink) {
+            if !self.options.quiet {
+                writeln!(out, "{}", line)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Scans every transaction in the block, grouping text output per
+    /// transaction. Each transaction is parsed just before it is scanned.
+    /// `header` runs first, with what is known of the block.
+    pub fn block_stream(
+        &mut self,
+        stream: BlockStream,
+        options: &ScanOptions,
+        header: impl FnOnce(&mut Output, &BlockInfo) -> Result<(), AppError>,
+    ) -> Result<(), AppError> {
+        let mut visitor = StreamScan {
+            output: self,
+            options,
+            header: Some(header),
+            scan: None,
+            error: None,
+        };
+        let visited = stream.visit(&mut visitor);
+        let StreamScan { scan, error, .. } = visitor;
+        // What was scanned before a parse error is kept.
+        if let Some(scan) = scan {
+            self.end_block(scan)?;
+        }
+        match error {
+            Some(e) => Err(e),
+            None => Ok(visited?),
+        }
+    }
+
+    fn begin_block(
+        &mut self,
+        height: Option<u64>,
+        tx_count: Option<usize>,
+    ) -> Result<BlockScan, AppError> {
+        let bar = match tx_count {
+            Some(len) if self.options.progress => progress::bar(len as u64, "transactions"),
+            _ => ProgressBar::hidden(),
+        };
+        // One database transaction per block keeps inserts fast.
+        if let Some(db) = &self.db {
+            db.begin()?;
+        }
+        Ok(BlockScan {
+            height,
+            index: 0,
+            bar,
+        })
+    }
+
+    /// Scans the next transaction of the block, returning whether to go on.
+    fn block_transaction(
+        &mut self,
+        scan: &mut BlockScan,
+        tx: &Transaction,
+        options: &ScanOptions,
+    ) -> Result<bool, AppError> {
+        if self.done() {
+            return Ok(false);
+        }
+        let index = scan.index;
+        scan.index += 1;
+        if let (0, Format::Text, Sink::Plain(out)) = (index, self.options.format, &mut self.sink) {
+            if let Some(pool) = identify_coinbase_pool(tx).filter(|_| !self.options.quiet) {
+                writeln!(out, "Mined by: {}", pool)?;
+            }
+        }
+        scan.bar.inc(1);
+        let (findings, more) = self.scanned(check_block_transaction(tx, index, options));
+        if !findings.is_empty() || self.options.show_empty {
+            let height = scan.height;
+            scan.bar
+                .suspend(|| self.write_transaction(height, index, &tx.hash, findings, more))?;
+        }
+        Ok(true)
+    }
+
+    fn end_block(&mut self, scan: BlockScan) -> Result<(), AppError> {
+        scan.bar.finish_and_clear();
+        if let Some(db) = &self.db {
+            db.commit()?;
+        }
+        Ok(())
+    }
+
+    fn write_transaction(
+        &mut self,
+        height: Option<u64>,
+        index: usize,
+        hash: &str,
+        findings: Vec<Finding>,
+        more: usize,
+    ) -> Result<(), AppError> {
+        if let (Format::Text, Sink::Plain(out)) = (self.options.format, &mut self.sink) {
+            if findings.is_empty() {
+                writeln!(out, "[{}] {}: No hidden messages found.", index, hash)?;
+                return Ok(());
+            }
+
+            if !self.options.quiet {
+                writeln!(out, "Transaction {}:", index)?;
+            }
+            let prefix = format!("[{}] {}: ", index, hash);
+            for finding in &findings {
+                let line = self.style.line(&prefix, finding, height, Some(index));
+                writeln!(out, "{}", line)?;
+            }
+        }
+        self.write_more(&format!("[{}] {}: ", index, hash), more)?;
+        self.record(height, Some(index), findings)
+    }
+
+    /// Reports the findings of a single, explicitly selected transaction.
+    /// Height and index are `None` for transactions fetched by txid.
+    pub fn transaction(
+        &mut self,
+        height: Option<u64>,
+        index: Option<usize>,
+        findings: Vec<Finding>,
+    ) -> Result<(), AppError> {
+        let (findings, more) = self.scanned(findings);
+        if let (Format::Text, Sink::Plain(out)) = (self.options.format, &mut self.sink) {
+            if self.options.quiet {
+                for finding in &findings {
+                    writeln!(out, "{}", self.style.line("", finding, height, index))?;
+                }
+            } else if !findings.is_empty() {
+                writeln!(out, "Hidden messages found:")?;
+                for finding in &findings {
+                    writeln!(out, "{}", self.style.line("", finding, height, index))?;
+                }
+            } else {
+                writeln!(out, "No hidden messages found in this transaction.")?;
+            }
+        }
+        self.write_more("", more)?;
+        self.record(height, index, findings)
+    }
+
+    /// Reports one transaction of a batch, tagging every line with its txid.
+    pub fn tagged_transaction(
+        &mut self,
+        txid: &str,
+        findings: Vec<Finding>,
+    ) -> Result<(), AppError> {
+        let (findings, more) = self.scanned(findings);
+        if let (Format::Text, Sink::Plain(out)) = (self.options.format, &mut self.sink) {
+            if findings.is_empty() && self.options.show_empty {
+                writeln!(out, "{}: No hidden messages found.", txid)?;
+            }
+            let prefix = format!("{}: ", txid);
+            for finding in &findings {
+                writeln!(out, "{}", self.style.line(&prefix, finding, None, None))?;
+            }
+        }
+        self.write_more(&format!("{}: ", txid), more)?;
+        self.record(None, None, findings)
+    }
+
+    /// With `--first-only`, whether the finding has been reported and the
+    /// scan can stop.
+    pub fn done(&self) -> bool {
+        self.options.first_only && self.summary.findings > 0
+    }
+
+    /// Accounts for one scanned transaction and returns the findings to report,
+    /// along with the number held back by `--limit-per-tx`.
+    fn scanned(&mut self, findings: Vec<Finding>) -> (Vec<Finding>, usize) {
+        self.summary.transactions += 1;
+        let mut findings = self.dedupe(findings);
+        if self.options.first_only {
+            findings.truncate(if self.done() { 0 } else { 1 });
+            return (findings, 0);
+        }
+        let more = match self.options.limit_per_tx {
+            Some(limit) if findings.len() > limit => {
+                let more = findings.len() - limit;
+                findings.truncate(limit);
+                more
+            }
+            _ => 0,
+        };
+        (findings, more)
+    }
+
+    /// Notes findings held back by `--limit-per-tx`, after the reported ones
+    /// in text output and on stderr otherwise.
+    fn write_more(&mut self, prefix: &str, more: usize) -> Result<(), AppError> {
+        if more == 0 {
+            return Ok(());
+        }
+        let note = format!("{}... and {} more", prefix, more);
+        match (self.options.format, &mut self.sink) {
+            (Format::Text, Sink::Plain(out)) => writeln!(out, "{}", note)?,
+            _ => eprintln!("{}", note),
+        }
+        Ok(())
+    }
+
+    /// With `--unique`, counts every message and keeps only first occurrences.
+    /// Findings without a message are all kept.
+    fn dedupe(&mut self, mut findings: Vec<Finding>) -> Vec<Finding> {
+        if self.options.unique {
+            findings.retain(|finding| {
+                if !finding.has_message() {
+                    return true;
+                }
+                let count = self.seen.entry(finding.message.clone()).or_insert(0);
+                *count += 1;
+                *count == 1
+            });
+        }
+        findings
+    }
+
+    fn write_unique_counts(&mut self) -> Result<(), AppError> {
+        let mut counts: Vec<_> = self.seen.iter().collect();
+        counts.sort_by(|a, b| b.1.cmp(a.1).then_with(|| a.0.cmp(b.0)));
+
+        let mut report = format!("{} unique message(s):\n", counts.len());
+        for (message, count) in counts {
+            report.push_str(&format!("{:>6}x {}\n", count, message));
+        }
+
+        match (self.options.format, &mut self.sink) {
+            (Format::Text, Sink::Plain(out)) => write!(out, "{}", report)?,
+            _ => eprint!("{}", report),
+        }
+        Ok(())
+    }
+
+    /// Text output gets the summary as a footer, NDJSON as a final object
+    /// after the findings, and CSV on stderr. JSON output holds it next to
+    /// the findings, see [`Output::finish`].
+    fn write_summary(&mut self) -> Result<(), AppError> {
+        match (self.options.format, &mut self.sink) {
+            (Format::Text, Sink::Plain(out)) => write!(out, "{}", self.summary)?,
+            (Format::Json, Sink::Plain(_)) => {}
+            (Format::Ndjson, Sink::Plain(out)) => {
+                let footer = Footer {
+                    summary: &self.summary,
+                };
+                writeln!(out, "{}", serde_json::to_string(&footer)?)?
+            }
+            _ => eprint!("{}", self.summary),
+        }
+        Ok(())
+    }
+
+    fn record(
+        &mut self,
+        height: Option<u64>,
+        index: Option<usize>,
+        mut findings: Vec<Finding>,
+    ) -> Result<(), AppError> {
+        if let Some(name) = &self.source_file {
+            for finding in &mut findings {
+                finding.source_file = Some(name.clone());
+            }
+        }
+        self.summary.count(&findings);
+        if let Some(db) = &self.db {
+            db.insert(height, &findings)?;
+        }
+        if let Some(dumper) = &self.dumper {
+            for finding in &findings {
+                dumper.dump(finding)?;
+            }
+        }
+        if let Some(webhook) = &self.webhook {
+            for finding in &findings {
+                // Sending only fails if the delivery task has died.
+                let _ = webhook.send(Event {
+                    block_height: height,
+                    finding: finding.clone(),
+                });
+            }
+        }
+        if let Sink::Csv(writer) = &mut self.sink {
+            let height = height.map(|h| h.to_string()).unwrap_or_default();
+            let index = index.map(|i| i.to_string()).unwrap_or_default();
+            for finding in &findings {
+                writer
+                    .write_record([
+                        height.as_str(),
+                        &index,
+                        &finding.tx_hash,
+                        &finding.location.to_string(),
+                        &finding.message,
+                    ])
+                    .map_err(io::Error::from)?;
+            }
+        }
+        if let (Format::Ndjson, Sink::Plain(out)) = (self.options.format, &mut self.sink) {
+            for finding in &findings {
+                writeln!(out, "{}", serde_json::to_string(finding)?)?;
+            }
+            out.flush()?;
+        }
+        if self.options.format == Format::Json {
+            self.findings.extend(findings);
+        }
+        Ok(())
+    }
+
+    /// Writes buffered output through to the file or terminal.
+    pub fn flush(&mut self) -> Result<(), AppError> {
+        match &mut self.sink {
+            Sink::Plain(writer) => writer.flush()?,
+            Sink::Csv(writer) => writer.flush()?,
+        }
+        Ok(())
+    }
+
+    /// Writes everything that is only known at the end of the run and returns
+    /// the number of findings that were reported.
+    pub fn finish(mut self) -> Result<usize, AppError> {
+        if self.options.unique {
+            self.write_unique_counts()?;
+        }
+        let summarized = self.options.summary || self.options.clusters;
+        if summarized {
+            self.summary.cluster();
+        }
+        if let (Format::Json, Sink::Plain(out)) = (self.options.format, &mut self.sink) {
+            let json = if summarized {
+                serde_json::to_string_pretty(&Summarized {
+                    findings: &self.findings,
+                    summary: &self.summary,
+                })?
+            } else {
+                serde_json::to_string_pretty(&self.findings)?
+            };
+            writeln!(out, "{}", json)?;
+        }
+        if summarized {
+            self.write_summary()?;
+        }
+        self.flush()?;
+        Ok(self.summary.findings)
+    }
+}
+
+/// Where a scan is within a block.
+struct BlockScan {
+    height: Option<u64>,
+    /// Index of the next transaction.
+    index: usize,
+    bar: ProgressBar,
+}
+
+/// Feeds a [`BlockStream`] to [`Output`], keeping the first error to return
+/// once the stream stops.
+struct StreamScan<'a, H> {
+    output: &'a mut Output,
+    options: &'a ScanOptions,
+    header: Option<H>,
+    scan: Option<BlockScan>,
+    error: Option<AppError>,
+}
+
+impl<H> BlockVisitor for StreamScan<'_, H>
+where
+    H: FnOnce(&mut Output, &BlockInfo) -> Result<(), AppError>,
+{
+    fn start(&mut self, info: &BlockInfo) {
+        let header = self
+            .header
+            .take()
+            .map_or(Ok(()), |header| header(self.output, info));
+        match header.and_then(|()| self.output.begin_block(info.height, info.tx_count)) {
+            Ok(scan) => self.scan = Some(scan),
+            Err(e) => self.error = Some(e),
+        }
+    }
+
+    fn transaction(&mut self, tx: Transaction) -> bool {
+        let Some(scan) = &mut self.scan else {
+            return false;
+        };
+        match self.output.block_transaction(scan, &tx, self.options) {
+            Ok(more) => more,
+            Err(e) => {
+                self.error = Some(e);
+                false
+            }
+        }
+    }
+}
@@ -0,0 +1,64 @@
+use crate::{printable_runs, Block, Transaction};
+
+/// Known coinbase tag fragments and the pool they identify, matched
+/// case-insensitively in order.
+const POOL_TAGS: &[(&str, &str)] = &[
+    ("foundry usa", "Foundry USA"),
+    ("antpool", "AntPool"),
+    ("f2pool", "F2Pool"),
+    ("七彩神仙鱼", "F2Pool"),
+    ("viabtc", "ViaBTC"),
+    ("binance", "Binance Pool"),
+    ("poolin", "Poolin"),
+    ("mara pool", "MARA Pool"),
+    ("marathon", "MARA Pool"),
+    ("luxor", "Luxor"),
+    ("spiderpool", "SpiderPool"),
+    ("sbicrypto", "SBI Crypto"),
+    ("sbi crypto", "SBI Crypto"),
+    ("secpool", "SECPOOL"),
+    ("ultimuspool", "ULTIMUSPOOL"),
+    ("ocean.xyz", "OCEAN"),
+    ("braiins", "Braiins Pool"),
+    ("slush", "Braiins Pool"),
+    ("btc.com", "BTC.com"),
+    ("btc.top", "BTC.TOP"),
+    ("huobi", "Huobi Pool"),
+    ("okex", "OKExPool"),
+    ("1thash", "1THash"),
+    ("bitfury", "BitFury"),
+    ("titan.io", "Titan"),
+    ("kano", "KanoPool"),
+    ("ghash.io", "GHash.IO"),
+    ("btc guild", "BTC Guild"),
+    ("eligius", "Eligius"),
+    ("bitminter", "BitMinter"),
+    ("50btc", "50BTC"),
+];
+
+/// Returns the mining pool whose tag appears in a coinbase message.
+pub fn identify_pool(coinbase_message: &str) -> Option<String> {
+    let message = coinbase_message.to_lowercase();
+    POOL_TAGS
+        .iter()
+        .find(|(tag, _)| message.contains(tag))
+        .map(|(_, pool)| pool.to_string())
+}
+
+/// Identifies the pool that mined a block from the printable parts of its
+/// coinbase, independent of any message filters.
+pub fn identify_block_pool(block: &Block) -> Option<String> {
+    identify_coinbase_pool(block.tx.first()?)
+}
+
+/// Like [`identify_block_pool`], given the block's coinbase transaction.
+pub fn identify_coinbase_pool(tx: &Transaction) -> Option<String> {
+    let coinbase = tx.vin.first()?.coinbase.as_deref()?;
+    let bytes = hex::decode(coinbase).ok()?;
+    let text = String::from_utf8_lossy(&bytes);
+    identify_pool(&text).or_else(|| {
+        printable_runs(&bytes, 3)
+            .iter()
+            .find_map(|run| identify_pool(run))
+    })
+}
@@ -0,0 +1,58 @@
+use std::fmt;
+use std::time::Duration;
+
+/// How long one block of a range scan took.
+#[derive(Clone, Copy, Debug)]
+pub struct BlockTiming {
+    pub height: u64,
+    /// From starting the fetch to having the block ready to scan, including
+    /// retries and waiting for the rate limit. Blocks streamed from the API
+    /// are ready once the response starts.
+    pub fetch: Duration,
+    /// Scanning, including receiving the rest of a streamed block.
+    pub scan: Duration,
+}
+
+/// The report printed by `--profile`: the total time spent fetching and
+/// scanning, and the slowest blocks.
+pub struct Profile<'a> {
+    timings: &'a [BlockTiming],
+    slowest: usize,
+}
+
+impl<'a> Profile<'a> {
+    pub fn new(timings: &'a [BlockTiming], slowest: usize) -> Self {
+        Profile { timings, slowest }
+    }
+}
+
+fn secs(duration: Duration) -> f64 {
+    duration.as_secs_f64()
+}
+
+impl fmt::Display for Profile<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let fetch: Duration = self.timings.iter().map(|t| t.fetch).sum();
+        let scan: Duration = self.timings.iter().map(|t| t.scan).sum();
+        // Concurrent fetches overlap, so their sum can exceed the wall time.
+        writeln!(
+            f,
+            "Profile of {} block(s): {:.2}s fetching, {:.2}s scanning",
+            self.timings.len(),
+            secs(fetch),
+            secs(scan)
+        )?;
+        let mut slowest: Vec<&BlockTiming> = self.timings.iter().collect();
+        slowest.sort_by_key(|t| std::cmp::Reverse(t.fetch + t.scan));
+        for timing in slowest.into_iter().take(self.slowest) {
+            writeln!(
+                f,
+                "  block {}: {:.3}s fetch, {:.3}s scan",
+                timing.height,
+                secs(timing.fetch),
+                secs(timing.scan)
+            )?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,24 @@
+use std::io;
+
+use indicatif::{ProgressBar, ProgressStyle};
+
+/// Whether progress bars should be drawn: only when stderr is a terminal, so
+/// piped and redirected runs stay clean.
+pub fn enabled() -> bool {
+    termion::is_tty(&io::stderr())
+}
+
+/// Creates a progress bar over `len` items, or a hidden one if
+/// [`enabled`] is false.
+pub fn bar(len: u64, unit: &str) -> ProgressBar {
+    if !enabled() {
+        return ProgressBar::hidden();
+    }
+    let template = format!(
+        "{{spinner}} [{{elapsed_precise}}] {{bar:40}} {{pos}}/{{len}} {} (ETA {{eta}})",
+        unit
+    );
+    let style =
+        ProgressStyle::with_template(&template).unwrap_or_else(|_| ProgressStyle::default_bar());
+    ProgressBar::new(len).with_style(style)
+}
@@ -0,0 +1,16 @@
+/// Payload prefixes of meta-protocols that use OP_RETURN outputs.
+const PROTOCOL_PREFIXES: &[(&[u8], &str)] = &[
+    (b"CNTRPRTY", "Counterparty"),
+    (b"omni", "Omni"),
+    (b"DOCPROOF", "Proof of Existence"),
+    (b"ASCRIBESPOOL", "Ascribe"),
+    (b"EW ", "Eternity Wall"),
+];
+
+/// Identifies the meta-protocol an OP_RETURN payload belongs to from its prefix.
+pub fn identify_protocol(payload: &[u8]) -> Option<&'static str> {
+    PROTOCOL_PREFIXES
+        .iter()
+        .find(|(prefix, _)| payload.starts_with(prefix))
+        .map(|(_, name)| *name)
+}
@@ -0,0 +1,61 @@
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// A token bucket allowing `per_sec` requests per second on average, with
+/// bursts of up to one second's worth. Shared by all tasks of a run, so the
+/// limit holds however many requests are in flight.
+#[derive(Debug)]
+pub struct RateLimiter {
+    per_sec: f64,
+    capacity: f64,
+    bucket: Mutex<Bucket>,
+}
+
+#[derive(Debug)]
+struct Bucket {
+    tokens: f64,
+    refilled: Instant,
+}
+
+impl RateLimiter {
+    /// Returns `None` unless `per_sec` is a positive number.
+    pub fn new(per_sec: f64) -> Option<Self> {
+        if !(per_sec.is_finite() && per_sec > 0.0) {
+            return None;
+        }
+        let capacity = per_sec.max(1.0);
+        Some(RateLimiter {
+            per_sec,
+            capacity,
+            bucket: Mutex::new(Bucket {
+                tokens: capacity,
+                refilled: Instant::now(),
+            }),
+        })
+    }
+
+    /// Waits until a request may be sent and takes its token.
+    pub async fn acquire(&self) {
+        while let Some(wait) = self.try_acquire() {
+            tokio::time::sleep(wait).await;
+        }
+    }
+
+    /// Takes a token if one is available, or else returns how long until it
+    /// will be.
+    fn try_acquire(&self) -> Option<Duration> {
+        let mut bucket = self.bucket.lock().unwrap_or_else(|e| e.into_inner());
+        let now = Instant::now();
+        let elapsed = now.duration_since(bucket.refilled).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * self.per_sec).min(self.capacity);
+        bucket.refilled = now;
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            None
+        } else {
+            Some(Duration::from_secs_f64(
+                (1.0 - bucket.tokens) / self.per_sec,
+            ))
+        }
+    }
+}
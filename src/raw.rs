@@ -0,0 +1,117 @@
+use bitcoin::consensus::encode;
+use bitcoin::Script;
+
+use crate::{Block, FetchError, ScriptPubKey, ScriptSig, Transaction, Vin, Vout};
+
+/// Decodes a hex-encoded serialized block into the transaction types used
+/// by the scanner.
+pub fn parse_block_hex(hex: &str) -> Result<Vec<Transaction>, FetchError> {
+    Ok(parse_raw_block_hex(hex)?.tx)
+}
+
+/// Like [`parse_block_hex`], but also recovers the BIP34 height if present.
+pub fn parse_raw_block_hex(hex: &str) -> Result<Block, FetchError> {
+    let bytes = hex::decode(hex.trim()).map_err(|e| FetchError::RawBlock(e.to_string()))?;
+    parse_raw_block(&bytes)
+}
+
+/// Decodes a consensus-serialized block.
+pub fn parse_raw_block(bytes: &[u8]) -> Result<Block, FetchError> {
+    let block: bitcoin::Block =
+        encode::deserialize(bytes).map_err(|e| FetchError::RawBlock(e.to_string()))?;
+
+    Ok(Block {
+        height: block.bip34_block_height().ok(),
+        tx: block.txdata.iter().map(Transaction::from).collect(),
+    })
+}
+
+/// Decodes a hex-encoded serialized transaction.
+pub fn parse_raw_transaction_hex(hex: &str) -> Result<Transaction, FetchError> {
+    let bytes = hex::decode(hex.trim()).map_err(|e| FetchError::RawBlock(e.to_string()))?;
+    let tx: bitcoin::Transaction =
+        encode::deserialize(&bytes).map_err(|e| FetchError::RawBlock(e.to_string()))?;
+    Ok(Transaction::from(&tx))
+}
+
+/// Names a script the way blockchain.info reports `scriptPubKey.type`.
+fn script_type(script: &Script) -> &'static str {
+    if script.is_op_return() {
+        "nulldata"
+    } else if script.is_p2pk() {
+        "pubkey"
+    } else if script.is_p2pkh() {
+        "pubkeyhash"
+    } else if script.is_p2sh() {
+        "scripthash"
+    } else if script.is_multisig() {
+        "multisig"
+    } else if script.is_p2wpkh() {
+        "witness_v0_keyhash"
+    } else if script.is_p2wsh() {
+        "witness_v0_scripthash"
+    } else if script.is_p2tr() {
+        "witness_v1_taproot"
+    } else {
+        "nonstandard"
+    }
+}
+
+impl From<&bitcoin::Transaction> for Transaction {
+    fn from(tx: &bitcoin::Transaction) -> Self {
+        let is_coinbase = tx.is_coinbase();
+
+        let vin = tx
+            .input
+            .iter()
+            .map(|input| {
+                let script_hex = hex::encode(input.script_sig.as_bytes());
+                let txinwitness = input.witness.iter().map(hex::encode).collect();
+                if is_coinbase {
+                    Vin {
+                        coinbase: Some(script_hex),
+                        txid: None,
+                        vout: None,
+                        script_sig: None,
+                        sequence: Some(input.sequence.0 as u64),
+                        txinwitness,
+                    }
+                } else {
+                    Vin {
+                        coinbase: None,
+                        txid: Some(input.previous_output.txid.to_string()),
+                        vout: Some(input.previous_output.vout),
+                        script_sig: Some(ScriptSig {
+                            asm: Some(input.script_sig.to_asm_string()),
+                            hex: Some(script_hex),
+                        }),
+                        sequence: Some(input.sequence.0 as u64),
+                        txinwitness,
+                    }
+                }
+            })
+            .collect();
+
+        let vout = tx
+            .output
+            .iter()
+            .enumerate()
+            .map(|(n, output)| Vout {
+                value: Some(output.value.to_btc()),
+                n: Some(n as u32),
+                script_pub_key: Some(ScriptPubKey {
+                    asm: Some(output.script_pubkey.to_asm_string()),
+                    hex: Some(hex::encode(output.script_pubkey.as_bytes())),
+                    script_type: Some(script_type(&output.script_pubkey).to_string()),
+                }),
+            })
+            .collect();
+
+        Transaction {
+            hash: tx.compute_txid().to_string(),
+            hex: Some(encode::serialize_hex(tx)),
+            vin,
+            vout,
+        }
+    }
+}
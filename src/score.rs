@@ -0,0 +1,51 @@
+use std::collections::{BTreeMap, HashSet};
+use std::sync::LazyLock;
+
+/// Shannon entropy of the message in bits per character. English text is
+/// typically around 4, random printable bytes approach `log2(95)` ≈ 6.6.
+pub fn shannon_entropy(s: &str) -> f64 {
+    let mut counts = BTreeMap::new();
+    let mut total = 0usize;
+    for c in s.chars() {
+        *counts.entry(c).or_insert(0usize) += 1;
+        total += 1;
+    }
+    if total == 0 {
+        return 0.0;
+    }
+
+    counts
+        .values()
+        .map(|&count| {
+            let p = count as f64 / total as f64;
+            -p * p.log2()
+        })
+        .sum()
+}
+
+const WORD_LIST: &str = include_str!("words.txt");
+
+/// [`WORD_LIST`] as a set, built on first use.
+static WORDS: LazyLock<HashSet<&'static str>> = LazyLock::new(|| WORD_LIST.lines().collect());
+
+fn is_english_word(word: &str) -> bool {
+    WORDS.contains(word)
+}
+
+/// Estimates how English a message is as the fraction of its non-whitespace
+/// characters that belong to words from a small bundled word list. Returns a
+/// value between 0.0 (gibberish, base58, hex) and 1.0 (plain English).
+pub fn english_score(s: &str) -> f64 {
+    let total = s.chars().filter(|c| !c.is_whitespace()).count();
+    if total == 0 {
+        return 0.0;
+    }
+
+    let in_words: usize = s
+        .split(|c: char| !c.is_ascii_alphabetic())
+        .filter(|token| is_english_word(&token.to_ascii_lowercase()))
+        .map(str::len)
+        .sum();
+
+    in_words as f64 / total as f64
+}
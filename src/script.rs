@@ -0,0 +1,142 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+pub const OP_0: u8 = 0x00;
+pub const OP_PUSHDATA1: u8 = 0x4c;
+pub const OP_PUSHDATA2: u8 = 0x4d;
+pub const OP_PUSHDATA4: u8 = 0x4e;
+pub const OP_RETURN: u8 = 0x6a;
+
+/// A single parsed script element.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Instruction<'a> {
+    /// Data pushed by `OP_0`, a direct push (`0x01..=0x4b`) or `OP_PUSHDATA1/2/4`.
+    Push(&'a [u8]),
+    /// Any other opcode.
+    Op(u8),
+}
+
+/// Splits a script into pushes and opcodes. Returns `None` if a push runs past
+/// the end of the script.
+pub fn instructions(script: &[u8]) -> Option<Vec<Instruction<'_>>> {
+    let mut result = Vec::new();
+    let mut pos = 0;
+
+    while pos < script.len() {
+        let opcode = script[pos];
+        pos += 1;
+
+        let len = match opcode {
+            OP_0 => 0,
+            0x01..=0x4b => opcode as usize,
+            OP_PUSHDATA1 => read_le(script, &mut pos, 1)?,
+            OP_PUSHDATA2 => read_le(script, &mut pos, 2)?,
+            OP_PUSHDATA4 => read_le(script, &mut pos, 4)?,
+            _ => {
+                result.push(Instruction::Op(opcode));
+                continue;
+            }
+        };
+
+        let end = pos.checked_add(len)?;
+        result.push(Instruction::Push(script.get(pos..end)?));
+        pos = end;
+    }
+
+    Some(result)
+}
+
+fn read_le(script: &[u8], pos: &mut usize, width: usize) -> Option<usize> {
+    let bytes = script.get(*pos..*pos + width)?;
+    *pos += width;
+    Some(
+        bytes
+            .iter()
+            .rev()
+            .fold(0usize, |acc, &b| (acc << 8) | b as usize),
+    )
+}
+
+/// Returns the concatenated data pushes following a leading `OP_RETURN`, or
+/// `None` if the script is not a well-formed `OP_RETURN` output.
+pub fn op_return_payload(script: &[u8]) -> Option<Vec<u8>> {
+    let (&first, rest) = script.split_first()?;
+    if first != OP_RETURN {
+        return None;
+    }
+
+    let mut payload = Vec::new();
+    for instruction in instructions(rest)? {
+        if let Instruction::Push(data) = instruction {
+            payload.extend_from_slice(data);
+        }
+    }
+    Some(payload)
+}
+
+/// Returns the data hidden in the fake public keys of a bare multisig
+/// output: the concatenated 33- and 65-byte pushes without their leading
+/// prefix byte, and for compressed keys without the trailing nonce byte too.
+/// `None` if there are no such pushes.
+pub fn p2ms_payload(script: &[u8]) -> Option<Vec<u8>> {
+    let mut payload = Vec::new();
+    for instruction in instructions(script)? {
+        if let Instruction::Push(data) = instruction {
+            match data.len() {
+                33 => payload.extend_from_slice(&data[1..32]),
+                // Uncompressed keys carry data right up to their last byte.
+                65 => payload.extend_from_slice(&data[1..]),
+                _ => {}
+            }
+        }
+    }
+    (!payload.is_empty()).then_some(payload)
+}
+
+/// Splits a BIP34 coinbase scriptSig into the block height it starts with and
+/// the remaining bytes, which usually hold the miner's tag.
+pub fn strip_bip34_height(coinbase: &[u8]) -> Option<(u64, &[u8])> {
+    let (&len, rest) = coinbase.split_first()?;
+    match len {
+        // OP_1..=OP_16 encode the tiny heights of the earliest BIP34 blocks.
+        0x51..=0x60 => Some(((len - 0x50) as u64, rest)),
+        0x01..=0x08 => {
+            let height = rest.get(..len as usize)?;
+            let value = height
+                .iter()
+                .rev()
+                .fold(0u64, |acc, &b| (acc << 8) | b as u64);
+            Some((value, &rest[len as usize..]))
+        }
+        _ => None,
+    }
+}
+
+/// Timestamp of the genesis block, the earliest plausible coinbase timestamp.
+const GENESIS_TIMESTAMP: u32 = 1_231_006_505;
+/// How far ahead of the local clock a block timestamp may be.
+const MAX_FUTURE_DRIFT: u64 = 2 * 60 * 60;
+
+/// Looks for a 4-byte little-endian unix timestamp among the pushes following
+/// the BIP34 height, as many pools include one next to their extranonce.
+pub fn coinbase_timestamp(coinbase: &[u8]) -> Option<u32> {
+    let (_, mut rest) = strip_bip34_height(coinbase)?;
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).ok()?.as_secs();
+    let latest = (now + MAX_FUTURE_DRIFT).min(u32::MAX as u64) as u32;
+
+    // Only direct pushes are followed; the miner tag that comes after is
+    // usually raw text rather than a well-formed push.
+    while let Some((&len, tail)) = rest.split_first() {
+        if !(0x01..=0x4b).contains(&len) {
+            break;
+        }
+        let data = tail.get(..len as usize)?;
+        if let Ok(bytes) = <[u8; 4]>::try_from(data) {
+            let timestamp = u32::from_le_bytes(bytes);
+            if (GENESIS_TIMESTAMP..=latest).contains(&timestamp) {
+                return Some(timestamp);
+            }
+        }
+        rest = &tail[len as usize..];
+    }
+    None
+}
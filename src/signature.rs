@@ -0,0 +1,99 @@
+use base64::prelude::*;
+use bitcoin::address::{Address, NetworkUnchecked};
+use bitcoin::secp256k1::Secp256k1;
+use bitcoin::sign_message::{signed_msg_hash, MessageSignature};
+use schemars::JsonSchema;
+use serde::Serialize;
+
+const ARMOR_BEGIN: &str = "-----BEGIN BITCOIN SIGNED MESSAGE-----";
+const ARMOR_SIGNATURE: &str = "-----BEGIN SIGNATURE-----";
+const ARMOR_END: &str = "-----END BITCOIN SIGNED MESSAGE-----";
+
+/// A message claiming to be signed by a Bitcoin address.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SignedMessage {
+    pub address: String,
+    /// Base64 of the 65-byte recoverable signature.
+    pub signature: String,
+    pub message: String,
+}
+
+/// Result of checking a [`SignedMessage`].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, JsonSchema)]
+pub struct SignatureCheck {
+    pub address: String,
+    pub valid: bool,
+}
+
+fn is_signature(s: &str) -> bool {
+    s.len() == 88
+        && BASE64_STANDARD
+            .decode(s)
+            .is_ok_and(|bytes| MessageSignature::from_slice(&bytes).is_ok())
+}
+
+fn is_address(s: &str) -> bool {
+    s.parse::<Address<NetworkUnchecked>>().is_ok()
+}
+
+/// Recognizes the armored format of Bitcoin Core's `signmessage`, and an
+/// address and base64 signature (in either order) followed by the message.
+pub fn parse_signed_message(text: &str) -> Option<SignedMessage> {
+    if let Some(armored) = parse_armored(text) {
+        return Some(armored);
+    }
+
+    let mut parts = text.trim().splitn(3, char::is_whitespace);
+    let (first, second) = (parts.next()?, parts.next()?);
+    let message = parts.next()?.trim_start().to_string();
+    let (address, signature) = if is_address(first) && is_signature(second) {
+        (first, second)
+    } else if is_signature(first) && is_address(second) {
+        (second, first)
+    } else {
+        return None;
+    };
+    Some(SignedMessage {
+        address: address.to_string(),
+        signature: signature.to_string(),
+        message,
+    })
+}
+
+fn parse_armored(text: &str) -> Option<SignedMessage> {
+    let body = text.trim().strip_prefix(ARMOR_BEGIN)?;
+    let (message, rest) = body.split_once(ARMOR_SIGNATURE)?;
+    let rest = rest.trim().strip_suffix(ARMOR_END)?;
+    let mut lines = rest.lines().map(str::trim).filter(|line| !line.is_empty());
+    let (address, signature) = (lines.next()?, lines.next()?);
+    (is_address(address) && is_signature(signature)).then(|| SignedMessage {
+        address: address.to_string(),
+        signature: signature.to_string(),
+        message: message.trim_matches(['\r', '\n']).to_string(),
+    })
+}
+
+impl SignedMessage {
+    /// Verifies the signature against the address. Only P2PKH addresses can
+    /// sign messages; any other address is reported as invalid.
+    pub fn check(&self) -> SignatureCheck {
+        let valid = (|| {
+            let address = self
+                .address
+                .parse::<Address<NetworkUnchecked>>()
+                .ok()?
+                .assume_checked();
+            let bytes = BASE64_STANDARD.decode(&self.signature).ok()?;
+            let signature = MessageSignature::from_slice(&bytes).ok()?;
+            let hash = signed_msg_hash(&self.message);
+            signature
+                .is_signed_by_address(&Secp256k1::verification_only(), &address, hash)
+                .ok()
+        })()
+        .unwrap_or(false);
+        SignatureCheck {
+            address: self.address.clone(),
+            valid,
+        }
+    }
+}
@@ -0,0 +1,1296 @@
+use std::fmt;
+use std::fs::{self, File};
+use std::future::Future;
+use std::io::{self, BufReader};
+use std::ops::RangeInclusive;
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use reqwest::header::{HeaderValue, CONTENT_TYPE};
+use reqwest::{Client, Proxy, Response, StatusCode};
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use tokio::sync::mpsc;
+use tracing::debug;
+
+use crate::cache::{Cache, CacheWriter};
+use crate::ratelimit::RateLimiter;
+use crate::raw::{parse_raw_block, parse_raw_block_hex, parse_raw_transaction_hex};
+use crate::stream::BlockStream;
+use crate::{ApiResponse, Block, FetchError, ScriptPubKey, ScriptSig, Transaction, Vin, Vout};
+
+#[derive(Clone, Debug)]
+pub enum BlockId {
+    Height(u64),
+    Hash(String),
+}
+
+impl BlockId {
+    pub fn parse(s: &str) -> Option<BlockId> {
+        if let Ok(height) = s.parse::<u64>() {
+            Some(BlockId::Height(height))
+        } else if is_block_hash(s) {
+            Some(BlockId::Hash(s.to_string()))
+        } else {
+            None
+        }
+    }
+}
+
+impl fmt::Display for BlockId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BlockId::Height(height) => write!(f, "{}", height),
+            BlockId::Hash(hash) => write!(f, "{}", hash),
+        }
+    }
+}
+
+pub fn is_block_hash(s: &str) -> bool {
+    s.len() == 64 && s.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+#[async_trait]
+pub trait BlockSource: Sync {
+    async fn fetch(&self, id: BlockId) -> Result<Block, FetchError>;
+
+    /// Fetches a block for scanning one transaction at a time, parsing it as
+    /// the response arrives where the API allows. Sources that only get whole
+    /// blocks hand over the parsed block. See [`fetch_block_stream`] for
+    /// where streamed blocks can be visited.
+    async fn fetch_stream(&self, id: BlockId) -> Result<BlockStream, FetchError> {
+        Ok(BlockStream::Parsed(self.fetch(id).await?))
+    }
+
+    /// Fetches a single transaction by txid.
+    async fn fetch_transaction(&self, txid: &str) -> Result<Transaction, FetchError>;
+
+    /// Fetches the current chain tip. Never cached.
+    async fn tip(&self) -> Result<Tip, FetchError>;
+
+    /// The URLs [`BlockSource::fetch`] would request for `id`, without
+    /// fetching anything. Parts only known from earlier responses, like the
+    /// hash of a block given by height, are `<placeholders>`.
+    fn planned_urls(&self, id: &BlockId) -> Result<Vec<String>, FetchError>;
+}
+
+/// Placeholder for the hash of a block in [`BlockSource::planned_urls`].
+fn planned_hash(id: &BlockId) -> String {
+    match id {
+        BlockId::Hash(hash) => hash.clone(),
+        BlockId::Height(height) => format!("<hash of block {}>", height),
+    }
+}
+
+/// The most recent block of the chain.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+pub struct Tip {
+    pub height: u64,
+    pub hash: String,
+}
+
+async fn fetch_blockchain_info_tip(
+    client: &HttpClient,
+    network: Network,
+) -> Result<Tip, FetchError> {
+    if network != Network::Mainnet {
+        return Err(blockchain_info_unsupported(network));
+    }
+    get_json(client, None, "https://blockchain.info/latestblock").await
+}
+
+async fn fetch_mempool_tip(client: &HttpClient, api: &str) -> Result<Tip, FetchError> {
+    let height = get_text(client, None, &format!("{}/blocks/tip/height", api)).await?;
+    let hash = get_text(client, None, &format!("{}/blocks/tip/hash", api)).await?;
+    let height = height
+        .parse()
+        .map_err(|_| FetchError::Unsupported(format!("Invalid tip height '{}'", height)))?;
+    Ok(Tip { height, hash })
+}
+
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+const MAX_RETRIES: u32 = 3;
+const RETRY_BASE_DELAY: Duration = Duration::from_millis(500);
+
+/// Reads a block saved from the blockchain.info API, either as returned by
+/// `block-height` (`{"blocks": [...]}`) or by `rawblock` (a single block).
+pub fn load_block_file(path: &str) -> Result<Block, FetchError> {
+    let data = fs::read_to_string(path).map_err(|source| FetchError::File {
+        path: path.to_string(),
+        source,
+    })?;
+    parse_block_json(&data, path)
+}
+
+/// Opens a block file like [`load_block_file`] reads, to be parsed as it is
+/// scanned.
+pub fn open_block_file(path: &str) -> Result<BlockStream, FetchError> {
+    let file = File::open(path).map_err(|source| FetchError::File {
+        path: path.to_string(),
+        source,
+    })?;
+    Ok(BlockStream::Reader {
+        label: path.to_string(),
+        reader: Box::new(BufReader::new(file)),
+    })
+}
+
+fn parse_block_json(data: &str, label: &str) -> Result<Block, FetchError> {
+    if let Ok(resp) = serde_json::from_str::<ApiResponse>(data) {
+        return resp
+            .blocks
+            .into_iter()
+            .next()
+            .ok_or_else(|| FetchError::NoBlocks(label.to_string()));
+    }
+    Ok(serde_json::from_str::<Block>(data)?)
+}
+
+/// Header carrying the `--api-key` of authenticated endpoints.
+pub const API_KEY_HEADER: &str = "X-API-Key";
+
+/// Settings of the HTTP client shared by all requests of a run.
+#[derive(Clone, Debug)]
+pub struct ClientOptions {
+    pub timeout: Duration,
+    /// Replaces the default `bitcoin_message_finder/<version>` user agent.
+    pub user_agent: Option<String>,
+    /// Sent as [`API_KEY_HEADER`] with every API request, but not to the
+    /// webhook. Unset by default.
+    pub api_key: Option<String>,
+    /// `http://`, `https://` or `socks5://` proxy for all requests. Without
+    /// it, the `HTTP_PROXY`/`HTTPS_PROXY` environment variables apply.
+    pub proxy: Option<String>,
+    /// Average number of requests sent per second, across all concurrent
+    /// fetches. Unlimited by default.
+    pub max_requests_per_sec: Option<f64>,
+    /// Blocks larger than this many bytes fail with [`FetchError::TooLarge`]
+    /// before they are read into memory. Also caps every other response.
+    pub max_block_bytes: Option<u64>,
+}
+
+impl Default for ClientOptions {
+    fn default() -> Self {
+        ClientOptions {
+            timeout: DEFAULT_TIMEOUT,
+            user_agent: None,
+            api_key: None,
+            proxy: None,
+            max_requests_per_sec: None,
+            max_block_bytes: None,
+        }
+    }
+}
+
+/// The HTTP client shared by all requests of a run, with the limits every
+/// attempt made by [`get_with_retry`] is held to. Clones share the client.
+#[derive(Clone, Debug)]
+pub struct HttpClient {
+    client: Client,
+    /// Deadline of a single request attempt.
+    timeout: Duration,
+    /// Gates every request attempt. Shared by the clones, so the limit holds
+    /// across all sources and concurrent fetches built from one client.
+    limiter: Option<Arc<RateLimiter>>,
+    /// Largest response body read, and largest block accepted.
+    max_body_bytes: Option<u64>,
+    /// Value of [`API_KEY_HEADER`], added to each API request rather than
+    /// to the client, so requests made with [`HttpClient::inner`] such as
+    /// webhook posts never carry it.
+    api_key: Option<HeaderValue>,
+}
+
+impl HttpClient {
+    /// The underlying client, for requests made without [`get_with_retry`].
+    /// It sends no API key.
+    pub fn inner(&self) -> &Client {
+        &self.client
+    }
+
+    /// Deadline of a single request attempt.
+    pub fn timeout(&self) -> Duration {
+        self.timeout
+    }
+
+    /// Fails with [`FetchError::TooLarge`] if a block of `size` bytes at
+    /// `url` exceeds [`ClientOptions::max_block_bytes`].
+    fn check_block_size(&self, url: &str, size: u64) -> Result<(), FetchError> {
+        match self.max_body_bytes {
+            Some(limit) if size > limit => Err(FetchError::TooLarge {
+                url: url.to_string(),
+                limit,
+            }),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Builds the HTTP client shared by all requests of a run. Its timeout, rate
+/// limit and body limit also apply to every attempt made by
+/// [`get_with_retry`].
+pub fn build_client(options: &ClientOptions) -> Result<HttpClient, FetchError> {
+    let user_agent = options.user_agent.as_deref().unwrap_or(concat!(
+        env!("CARGO_PKG_NAME"),
+        "/",
+        env!("CARGO_PKG_VERSION")
+    ));
+
+    let api_key = match &options.api_key {
+        Some(key) => {
+            let mut value = HeaderValue::from_str(key)?;
+            value.set_sensitive(true);
+            Some(value)
+        }
+        None => None,
+    };
+
+    let mut builder = Client::builder()
+        .user_agent(user_agent)
+        .tcp_keepalive(Duration::from_secs(60));
+    if let Some(url) = &options.proxy {
+        let proxy = Proxy::all(url).map_err(|source| FetchError::InvalidProxy {
+            url: url.clone(),
+            source,
+        })?;
+        builder = builder.proxy(proxy);
+    }
+    Ok(HttpClient {
+        client: builder.build()?,
+        timeout: options.timeout,
+        limiter: options
+            .max_requests_per_sec
+            .and_then(RateLimiter::new)
+            .map(Arc::new),
+        max_body_bytes: options.max_block_bytes,
+        api_key,
+    })
+}
+
+/// Sends a GET request, retrying transport errors, timeouts, rate limiting
+/// and server errors with exponential backoff (500ms, 1s, 2s) before giving
+/// up. An attempt that hasn't read the whole response within the client
+/// timeout is abandoned. Returns the body of a successful response.
+pub async fn get_with_retry(client: &HttpClient, url: &str) -> Result<Vec<u8>, FetchError> {
+    let fetched = send_with_retry(client, url).await?;
+    if !fetched.status.is_success() {
+        return Err(FetchError::Status {
+            status: fetched.status.as_u16(),
+            snippet: snippet(&String::from_utf8_lossy(&fetched.body)).to_string(),
+        });
+    }
+    Ok(fetched.body)
+}
+
+/// A response read in full within one attempt.
+struct Fetched {
+    status: StatusCode,
+    content_type: Option<String>,
+    body: Vec<u8>,
+}
+
+fn content_type(resp: &Response) -> Option<String> {
+    resp.headers()
+        .get(CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .map(str::to_string)
+}
+
+/// Sends a GET of `url` and returns the response once its headers are in,
+/// giving up if the announced length exceeds the body limit.
+async fn open_once(client: &HttpClient, url: &str) -> Result<Response, FetchError> {
+    let mut request = client.client.get(url);
+    if let Some(key) = &client.api_key {
+        request = request.header(API_KEY_HEADER, key.clone());
+    }
+    let resp = request.send().await?;
+    if let Some(len) = resp.content_length() {
+        client.check_block_size(url, len)?;
+    }
+    Ok(resp)
+}
+
+/// Reads the rest of `resp`, giving up as soon as the received length
+/// exceeds the body limit.
+async fn read_body(
+    client: &HttpClient,
+    url: &str,
+    mut resp: Response,
+) -> Result<Fetched, FetchError> {
+    let status = resp.status();
+    let content_type = content_type(&resp);
+    let mut body = Vec::new();
+    while let Some(chunk) = resp.chunk().await? {
+        body.extend_from_slice(&chunk);
+        client.check_block_size(url, body.len() as u64)?;
+    }
+    Ok(Fetched {
+        status,
+        content_type,
+        body,
+    })
+}
+
+/// Like [`get_with_retry`], but returns error responses so their bodies can
+/// be reported.
+async fn send_with_retry(client: &HttpClient, url: &str) -> Result<Fetched, FetchError> {
+    retry(
+        client,
+        url,
+        || async { read_body(client, url, open_once(client, url).await?).await },
+        |fetched| fetched.status,
+    )
+    .await
+}
+
+/// A response to a GET, left unread if it is a successful JSON one.
+enum Opened {
+    Streamed(Response),
+    /// Any other response, read in full to be reported.
+    Read(Fetched),
+}
+
+/// Like [`send_with_retry`], but leaves the body of a successful JSON
+/// response to be read as it is parsed.
+async fn open_with_retry(client: &HttpClient, url: &str) -> Result<Opened, FetchError> {
+    retry(
+        client,
+        url,
+        || async {
+            let resp = open_once(client, url).await?;
+            let json = content_type(&resp).is_none_or(|content_type| content_type.contains("json"));
+            if resp.status().is_success() && json {
+                return Ok(Opened::Streamed(resp));
+            }
+            Ok(Opened::Read(read_body(client, url, resp).await?))
+        },
+        |opened| match opened {
+            Opened::Streamed(resp) => resp.status(),
+            Opened::Read(fetched) => fetched.status,
+        },
+    )
+    .await
+}
+
+/// Runs `attempt` with the retries of [`get_with_retry`], each attempt
+/// within the client timeout. `status` tells the status of a response.
+async fn retry<T, F: Future<Output = Result<T, FetchError>>>(
+    client: &HttpClient,
+    url: &str,
+    mut attempt: impl FnMut() -> F,
+    status: impl Fn(&T) -> StatusCode,
+) -> Result<T, FetchError> {
+    let mut attempt_number = 0;
+    loop {
+        if let Some(limiter) = &client.limiter {
+            limiter.acquire().await;
+        }
+        debug!(url, attempt = attempt_number, "GET");
+        let after = client.timeout;
+        let result = tokio::time::timeout(after, attempt())
+            .await
+            .unwrap_or_else(|_| {
+                Err(FetchError::Timeout {
+                    url: url.to_string(),
+                    after,
+                })
+            });
+        let retryable = match &result {
+            Ok(response) => {
+                let status = status(response);
+                debug!(url, %status, "response");
+                status.is_server_error() || status == StatusCode::TOO_MANY_REQUESTS
+            }
+            Err(e) => {
+                debug!(url, error = %e, "request failed");
+                !matches!(e, FetchError::TooLarge { .. })
+            }
+        };
+
+        if !retryable || attempt_number == MAX_RETRIES {
+            return result;
+        }
+
+        let delay = RETRY_BASE_DELAY * 2u32.pow(attempt_number);
+        debug!(url, ?delay, "retrying");
+        tokio::time::sleep(delay).await;
+        attempt_number += 1;
+    }
+}
+
+/// Chunks of a [`ResponseBody`] received ahead of the parser.
+const BODY_CHUNKS_BUFFERED: usize = 16;
+
+/// The body of a response, read as it is parsed, see
+/// [`BlockStream::Response`]. A task of its own receives it, giving up when
+/// no chunk arrives within the client timeout or the body limit is exceeded.
+pub struct ResponseBody {
+    chunks: mpsc::Receiver<Result<Vec<u8>, FetchError>>,
+    chunk: Vec<u8>,
+    read: usize,
+    /// Why receiving the body failed, which the parser only sees as an I/O
+    /// error.
+    error: Option<FetchError>,
+    /// Where the body goes once it parsed.
+    cache: Option<CacheWriter>,
+}
+
+impl ResponseBody {
+    fn spawn(
+        client: &HttpClient,
+        url: &str,
+        mut resp: Response,
+        cache: Option<CacheWriter>,
+    ) -> Self {
+        let (sender, chunks) = mpsc::channel(BODY_CHUNKS_BUFFERED);
+        let (client, url) = (client.clone(), url.to_string());
+        tokio::spawn(async move {
+            let mut received = 0;
+            loop {
+                let chunk = match tokio::time::timeout(client.timeout, resp.chunk()).await {
+                    Ok(Ok(Some(chunk))) => {
+                        received += chunk.len() as u64;
+                        client
+                            .check_block_size(&url, received)
+                            .map(|()| chunk.to_vec())
+                    }
+                    Ok(Ok(None)) => break,
+                    Ok(Err(e)) => Err(e.into()),
+                    Err(_) => Err(FetchError::Timeout {
+                        url: url.clone(),
+                        after: client.timeout,
+                    }),
+                };
+                let failed = chunk.is_err();
+                // The receiver is gone once the block was dropped unparsed.
+                if sender.send(chunk).await.is_err() || failed {
+                    break;
+                }
+            }
+        });
+        ResponseBody {
+            chunks,
+            chunk: Vec::new(),
+            read: 0,
+            error: None,
+            cache,
+        }
+    }
+
+    /// Settles a visit that read the body: an error receiving it replaces the
+    /// parse error it caused, and a body that parsed is cached.
+    pub(crate) fn finish(mut self, visited: Result<(), FetchError>) -> Result<(), FetchError> {
+        if let Err(e) = visited {
+            return Err(self.error.take().unwrap_or(e));
+        }
+        if let Some(cache) = self.cache.take() {
+            cache.commit();
+        }
+        Ok(())
+    }
+}
+
+impl io::Read for ResponseBody {
+    /// Blocks until the next chunk arrives, so it must not run on a task of
+    /// a current-thread runtime.
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while self.read == self.chunk.len() {
+            match self.chunks.blocking_recv() {
+                Some(Ok(chunk)) => {
+                    if let Some(cache) = &mut self.cache {
+                        cache.write(&chunk);
+                    }
+                    self.chunk = chunk;
+                    self.read = 0;
+                }
+                Some(Err(e)) => {
+                    let error = io::Error::other(e.to_string());
+                    self.error = Some(e);
+                    return Err(error);
+                }
+                None => return Ok(0),
+            }
+        }
+        let len = buf.len().min(self.chunk.len() - self.read);
+        buf[..len].copy_from_slice(&self.chunk[self.read..self.read + len]);
+        self.read += len;
+        Ok(len)
+    }
+}
+
+async fn text_with_retry(client: &HttpClient, url: &str) -> Result<String, FetchError> {
+    let body = get_with_retry(client, url).await?;
+    Ok(String::from_utf8_lossy(&body).into_owned())
+}
+
+/// Longest part of a response body included in logs and errors.
+const SNIPPET_LEN: usize = 200;
+
+fn snippet(body: &str) -> &str {
+    match body.char_indices().nth(SNIPPET_LEN) {
+        Some((end, _)) => &body[..end],
+        None => body,
+    }
+}
+
+/// Fetches `url`, or reads it from the cache, and parses the body. Only
+/// bodies that parse are cached.
+async fn get_parsed<T>(
+    client: &HttpClient,
+    cache: Option<&Cache>,
+    url: &str,
+    parse: impl FnOnce(u16, Option<&str>, &str) -> Result<T, FetchError>,
+) -> Result<T, FetchError> {
+    let cached = cache.and_then(|cache| cache.get(url));
+    let fresh = cached.is_none();
+    // Only successful responses are cached, so cached ones count as 200 OK.
+    let (status, content_type, body) = match cached {
+        Some(body) => {
+            // Responses cached before the limit was set are held to it too.
+            client.check_block_size(url, body.len() as u64)?;
+            (200, None, body)
+        }
+        None => {
+            let fetched = send_with_retry(client, url).await?;
+            let body = String::from_utf8_lossy(&fetched.body).into_owned();
+            (fetched.status.as_u16(), fetched.content_type, body)
+        }
+    };
+
+    let value = parse(status, content_type.as_deref(), &body).inspect_err(|e| {
+        debug!(url, error = %e, body = snippet(&body), "failed to parse response");
+    })?;
+    debug!(url, bytes = body.len(), "parsed response");
+    if let (true, Some(cache)) = (fresh, cache) {
+        cache.put(url, &body);
+    }
+    Ok(value)
+}
+
+/// Fetches `url` and parses the body as JSON, logging the start of the body
+/// when it does not parse.
+async fn get_json<T: DeserializeOwned>(
+    client: &HttpClient,
+    cache: Option<&Cache>,
+    url: &str,
+) -> Result<T, FetchError> {
+    get_parsed(client, cache, url, parse_json_response).await
+}
+
+/// Fetches the block JSON at `url` like [`get_json`], but leaves parsing it
+/// to [`BlockStream::visit`], which reads it as it arrives or from the
+/// cache. Like [`get_json`], a body is only cached once it parsed. A task of
+/// its own receives the response, so the stream must be visited on a
+/// multi-threaded runtime or outside the runtime. `label` names the block
+/// in errors.
+pub async fn fetch_block_stream(
+    client: &HttpClient,
+    cache: Option<&Cache>,
+    url: &str,
+    label: String,
+) -> Result<BlockStream, FetchError> {
+    let cached = cache
+        .and_then(|cache| cache.open(url))
+        .and_then(|file| Some((file.metadata().ok()?.len(), file)));
+    if let Some((size, file)) = cached {
+        client.check_block_size(url, size)?;
+        return Ok(BlockStream::Reader {
+            label,
+            reader: Box::new(BufReader::new(file)),
+        });
+    }
+    match open_with_retry(client, url).await? {
+        Opened::Streamed(resp) => {
+            let writer = cache.and_then(|cache| cache.writer(url));
+            let body = ResponseBody::spawn(client, url, resp, writer);
+            Ok(BlockStream::Response { label, body })
+        }
+        Opened::Read(fetched) => {
+            let body = String::from_utf8_lossy(&fetched.body).into_owned();
+            let status = fetched.status.as_u16();
+            check_json_response(status, fetched.content_type.as_deref(), &body).inspect_err(
+                |e| debug!(url, error = %e, body = snippet(&body), "failed to parse response"),
+            )?;
+            Ok(BlockStream::Json { label, body })
+        }
+    }
+}
+
+async fn get_text(
+    client: &HttpClient,
+    cache: Option<&Cache>,
+    url: &str,
+) -> Result<String, FetchError> {
+    get_parsed(client, cache, url, |status, _, body| {
+        if !(200..300).contains(&status) {
+            return Err(FetchError::Status {
+                status,
+                snippet: snippet(body).to_string(),
+            });
+        }
+        Ok(body.trim().to_string())
+    })
+    .await
+}
+
+/// Parses an API response body as JSON. Bodies that are not JSON, such as
+/// the HTML pages served while rate limiting, become [`FetchError::NonJson`]
+/// with the start of the body instead of an opaque parse error.
+pub fn parse_json_response<T: DeserializeOwned>(
+    status: u16,
+    content_type: Option<&str>,
+    body: &str,
+) -> Result<T, FetchError> {
+    check_json_response(status, content_type, body)?;
+    Ok(serde_json::from_str(body)?)
+}
+
+/// The checks of [`parse_json_response`] short of parsing.
+fn check_json_response(
+    status: u16,
+    content_type: Option<&str>,
+    body: &str,
+) -> Result<(), FetchError> {
+    let json_type = content_type.is_none_or(|content_type| content_type.contains("json"));
+    let trimmed = body.trim_start();
+    let json_body = trimmed.starts_with('{') || trimmed.starts_with('[');
+    if !json_type || !json_body {
+        return Err(FetchError::NonJson {
+            status,
+            snippet: snippet(body).to_string(),
+        });
+    }
+    if !(200..300).contains(&status) {
+        return Err(FetchError::Status {
+            status,
+            snippet: snippet(body).to_string(),
+        });
+    }
+    Ok(())
+}
+
+/// The Bitcoin network blocks are fetched from.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Network {
+    #[default]
+    Mainnet,
+    Testnet,
+    Signet,
+}
+
+impl Network {
+    pub fn parse(name: &str) -> Option<Network> {
+        match name {
+            "mainnet" | "bitcoin" => Some(Network::Mainnet),
+            "testnet" => Some(Network::Testnet),
+            "signet" => Some(Network::Signet),
+            _ => None,
+        }
+    }
+
+    /// Base URL of the mempool.space API for this network.
+    fn mempool_api(self) -> String {
+        match self {
+            Network::Mainnet => MEMPOOL_API.to_string(),
+            Network::Testnet => "https://mempool.space/testnet/api".to_string(),
+            Network::Signet => "https://mempool.space/signet/api".to_string(),
+        }
+    }
+}
+
+impl fmt::Display for Network {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Network::Mainnet => "mainnet",
+            Network::Testnet => "testnet",
+            Network::Signet => "signet",
+        };
+        f.write_str(name)
+    }
+}
+
+fn blockchain_info_unsupported(network: Network) -> FetchError {
+    FetchError::Unsupported(format!(
+        "blockchain.info only serves mainnet, use --backend mempool for {}",
+        network
+    ))
+}
+
+pub struct BlockchainInfoSource {
+    client: HttpClient,
+    network: Network,
+    cache: Option<Cache>,
+}
+
+impl BlockchainInfoSource {
+    pub fn new(client: HttpClient, network: Network) -> Self {
+        BlockchainInfoSource {
+            client,
+            network,
+            cache: None,
+        }
+    }
+
+    /// Reads and stores responses in `cache`.
+    pub fn with_cache(mut self, cache: Option<Cache>) -> Self {
+        self.cache = cache;
+        self
+    }
+}
+
+#[async_trait]
+impl BlockSource for BlockchainInfoSource {
+    async fn fetch(&self, id: BlockId) -> Result<Block, FetchError> {
+        if self.network != Network::Mainnet {
+            return Err(blockchain_info_unsupported(self.network));
+        }
+        match id {
+            BlockId::Height(height) => {
+                fetch_block_data(&self.client, self.cache.as_ref(), &height.to_string()).await
+            }
+            BlockId::Hash(hash) => {
+                fetch_block_by_hash(&self.client, self.cache.as_ref(), &hash).await
+            }
+        }
+    }
+
+    async fn fetch_stream(&self, id: BlockId) -> Result<BlockStream, FetchError> {
+        if self.network != Network::Mainnet {
+            return Err(blockchain_info_unsupported(self.network));
+        }
+        let (label, url) = match id {
+            BlockId::Height(height) => (height.to_string(), block_height_url(&height.to_string())),
+            BlockId::Hash(hash) => (hash.clone(), block_hash_url(&hash)),
+        };
+        fetch_block_stream(&self.client, self.cache.as_ref(), &url, label).await
+    }
+
+    async fn fetch_transaction(&self, txid: &str) -> Result<Transaction, FetchError> {
+        if self.network != Network::Mainnet {
+            return Err(blockchain_info_unsupported(self.network));
+        }
+        let url = format!("https://blockchain.info/rawtx/{}?format=json", txid);
+        get_json(&self.client, self.cache.as_ref(), &url).await
+    }
+
+    async fn tip(&self) -> Result<Tip, FetchError> {
+        fetch_blockchain_info_tip(&self.client, self.network).await
+    }
+
+    fn planned_urls(&self, id: &BlockId) -> Result<Vec<String>, FetchError> {
+        if self.network != Network::Mainnet {
+            return Err(blockchain_info_unsupported(self.network));
+        }
+        Ok(vec![match id {
+            BlockId::Height(height) => block_height_url(&height.to_string()),
+            BlockId::Hash(hash) => block_hash_url(hash),
+        }])
+    }
+}
+
+fn block_height_url(block_height: &str) -> String {
+    format!(
+        "https://blockchain.info/block-height/{}?format=json",
+        block_height
+    )
+}
+
+fn block_hash_url(block_hash: &str) -> String {
+    format!(
+        "https://blockchain.info/rawblock/{}?format=json",
+        block_hash
+    )
+}
+
+pub async fn fetch_block_data(
+    client: &HttpClient,
+    cache: Option<&Cache>,
+    block_height: &str,
+) -> Result<Block, FetchError> {
+    let url = block_height_url(block_height);
+    let resp = get_json::<ApiResponse>(client, cache, &url).await?;
+    let block = resp
+        .blocks
+        .into_iter()
+        .next()
+        .ok_or_else(|| FetchError::NoBlocks(block_height.to_string()))?;
+    Ok(block)
+}
+
+pub async fn fetch_block_by_hash(
+    client: &HttpClient,
+    cache: Option<&Cache>,
+    block_hash: &str,
+) -> Result<Block, FetchError> {
+    get_json(client, cache, &block_hash_url(block_hash)).await
+}
+
+/// Finds the first block at or below `tip` whose header time, in seconds since
+/// the Unix epoch, is at or after `timestamp`, by binary search with
+/// `block_time` looking up the time of a height. Returns `None` if even `tip`
+/// is older. Header times are not strictly increasing, so near `timestamp`
+/// the result can be off by a few blocks.
+pub async fn search_block_time(
+    tip: u64,
+    timestamp: u64,
+    mut block_time: impl AsyncFnMut(u64) -> Result<u64, FetchError>,
+) -> Result<Option<u64>, FetchError> {
+    // Blocks below `low` are older than `timestamp`, `high` and above are not.
+    let (mut low, mut high) = (0, tip + 1);
+    while low < high {
+        let mid = low + (high - low) / 2;
+        if block_time(mid).await? < timestamp {
+            low = mid + 1;
+        } else {
+            high = mid;
+        }
+    }
+    Ok(Some(low).filter(|&height| height <= tip))
+}
+
+/// [`search_block_time`] over the header times listed by mempool.space, which
+/// like [`fetch_block_sizes`] is used whatever the backend.
+pub async fn first_block_since(
+    client: &HttpClient,
+    network: Network,
+    timestamp: u64,
+    tip: u64,
+) -> Result<Option<u64>, FetchError> {
+    let api = network.mempool_api();
+    search_block_time(tip, timestamp, async |height| {
+        let url = format!("{}/v1/blocks/{}", api, height);
+        let page = get_json::<Vec<BlockTime>>(client, None, &url).await?;
+        page.into_iter()
+            .find(|block| block.height == height)
+            .map(|block| block.timestamp)
+            .ok_or(FetchError::NoBlocks(url))
+    })
+    .await
+}
+
+/// When a block was mined, as listed by mempool.space.
+#[derive(Deserialize)]
+struct BlockTime {
+    height: u64,
+    timestamp: u64,
+}
+
+const MEMPOOL_API: &str = "https://mempool.space/api";
+const MEMPOOL_PAGE_SIZE: usize = 25;
+
+/// Size of a block, as listed without its transactions.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+pub struct BlockSize {
+    pub height: u64,
+    /// Serialized size in bytes.
+    pub size: u64,
+    pub tx_count: usize,
+}
+
+/// Fetches the [`BlockSize`] of every block in `heights`, in height order,
+/// from mempool.space's block list, which covers 15 blocks per request.
+/// blockchain.info has no such list, so this is used whatever the backend.
+pub async fn fetch_block_sizes(
+    client: &HttpClient,
+    network: Network,
+    heights: RangeInclusive<u64>,
+) -> Result<Vec<BlockSize>, FetchError> {
+    let (start, end) = (*heights.start(), *heights.end());
+    let api = network.mempool_api();
+    let mut sizes = Vec::new();
+    let mut next = Some(end).filter(|_| start <= end);
+    // Each page lists the requested block and the ones below it.
+    while let Some(height) = next {
+        let url = format!("{}/v1/blocks/{}", api, height);
+        let page = get_json::<Vec<BlockSize>>(client, None, &url).await?;
+        let lowest = page.iter().map(|block| block.height).min();
+        sizes.extend(
+            page.into_iter()
+                .filter(|block| heights.contains(&block.height)),
+        );
+        next = match lowest {
+            Some(lowest) if lowest > start && lowest <= height => Some(lowest - 1),
+            Some(_) => None,
+            None => return Err(FetchError::NoBlocks(url)),
+        };
+    }
+    sizes.sort_by_key(|block| block.height);
+    sizes.dedup_by_key(|block| block.height);
+    Ok(sizes)
+}
+
+#[derive(Deserialize)]
+struct MempoolBlock {
+    #[serde(default)]
+    height: Option<u64>,
+    #[serde(default)]
+    size: Option<u64>,
+    tx_count: usize,
+}
+
+#[derive(Deserialize)]
+struct MempoolVin {
+    #[serde(default)]
+    txid: Option<String>,
+    #[serde(default)]
+    vout: Option<u32>,
+    #[serde(default)]
+    scriptsig: Option<String>,
+    #[serde(default)]
+    scriptsig_asm: Option<String>,
+    #[serde(default)]
+    witness: Vec<String>,
+    #[serde(default)]
+    is_coinbase: bool,
+    #[serde(default)]
+    sequence: Option<u64>,
+}
+
+#[derive(Deserialize)]
+struct MempoolVout {
+    #[serde(default)]
+    scriptpubkey: Option<String>,
+    #[serde(default)]
+    scriptpubkey_asm: Option<String>,
+    #[serde(default)]
+    scriptpubkey_type: Option<String>,
+    #[serde(default)]
+    value: Option<u64>,
+}
+
+#[derive(Deserialize)]
+struct MempoolTransaction {
+    txid: String,
+    #[serde(default)]
+    vin: Vec<MempoolVin>,
+    #[serde(default)]
+    vout: Vec<MempoolVout>,
+}
+
+/// Maps mempool.space script type names onto the names used by blockchain.info.
+fn mempool_script_type(script_type: &str) -> String {
+    match script_type {
+        "op_return" => "nulldata",
+        "p2pk" => "pubkey",
+        "p2pkh" => "pubkeyhash",
+        "p2sh" => "scripthash",
+        "v0_p2wpkh" => "witness_v0_keyhash",
+        "v0_p2wsh" => "witness_v0_scripthash",
+        "v1_p2tr" => "witness_v1_taproot",
+        other => other,
+    }
+    .to_string()
+}
+
+impl From<MempoolTransaction> for Transaction {
+    fn from(tx: MempoolTransaction) -> Self {
+        let vin = tx
+            .vin
+            .into_iter()
+            .map(|vin| {
+                if vin.is_coinbase {
+                    Vin {
+                        coinbase: vin.scriptsig,
+                        txid: None,
+                        vout: None,
+                        script_sig: None,
+                        sequence: vin.sequence,
+                        txinwitness: vin.witness,
+                    }
+                } else {
+                    Vin {
+                        coinbase: None,
+                        txid: vin.txid,
+                        vout: vin.vout,
+                        script_sig: Some(ScriptSig {
+                            asm: vin.scriptsig_asm,
+                            hex: vin.scriptsig,
+                        }),
+                        sequence: vin.sequence,
+                        txinwitness: vin.witness,
+                    }
+                }
+            })
+            .collect();
+
+        let vout = tx
+            .vout
+            .into_iter()
+            .enumerate()
+            .map(|(n, vout)| Vout {
+                value: vout.value.map(|sats| sats as f64 / 100_000_000.0),
+                n: Some(n as u32),
+                script_pub_key: Some(ScriptPubKey {
+                    asm: vout.scriptpubkey_asm,
+                    hex: vout.scriptpubkey,
+                    script_type: vout.scriptpubkey_type.as_deref().map(mempool_script_type),
+                }),
+            })
+            .collect();
+
+        Transaction {
+            hash: tx.txid,
+            hex: None,
+            vin,
+            vout,
+        }
+    }
+}
+
+pub struct MempoolSource {
+    client: HttpClient,
+    api: String,
+    cache: Option<Cache>,
+}
+
+impl MempoolSource {
+    pub fn new(client: HttpClient, network: Network) -> Self {
+        MempoolSource {
+            client,
+            api: network.mempool_api(),
+            cache: None,
+        }
+    }
+
+    /// Reads and stores responses in `cache`.
+    pub fn with_cache(mut self, cache: Option<Cache>) -> Self {
+        self.cache = cache;
+        self
+    }
+}
+
+#[async_trait]
+impl BlockSource for MempoolSource {
+    async fn fetch(&self, id: BlockId) -> Result<Block, FetchError> {
+        let block_hash = match id {
+            BlockId::Hash(hash) => hash,
+            BlockId::Height(height) => {
+                let url = format!("{}/block-height/{}", self.api, height);
+                get_text(&self.client, self.cache.as_ref(), &url).await?
+            }
+        };
+
+        let url = format!("{}/block/{}", self.api, block_hash);
+        let info = get_json::<MempoolBlock>(&self.client, self.cache.as_ref(), &url).await?;
+        // The transactions come in small pages, so only the block size tells.
+        if let Some(size) = info.size {
+            self.client.check_block_size(&url, size)?;
+        }
+
+        // mempool.space only returns a fixed number of transactions per page.
+        let mut tx = Vec::with_capacity(info.tx_count);
+        for start_index in (0..info.tx_count).step_by(MEMPOOL_PAGE_SIZE) {
+            let url = format!("{}/block/{}/txs/{}", self.api, block_hash, start_index);
+            let page = get_json::<Vec<MempoolTransaction>>(&self.client, self.cache.as_ref(), &url)
+                .await?;
+            tx.extend(page.into_iter().map(Transaction::from));
+        }
+
+        Ok(Block {
+            height: info.height,
+            tx,
+        })
+    }
+
+    async fn fetch_transaction(&self, txid: &str) -> Result<Transaction, FetchError> {
+        let url = format!("{}/tx/{}", self.api, txid);
+        let tx = get_json::<MempoolTransaction>(&self.client, self.cache.as_ref(), &url).await?;
+        Ok(Transaction::from(tx))
+    }
+
+    async fn tip(&self) -> Result<Tip, FetchError> {
+        fetch_mempool_tip(&self.client, &self.api).await
+    }
+
+    fn planned_urls(&self, id: &BlockId) -> Result<Vec<String>, FetchError> {
+        let mut urls = Vec::new();
+        if let BlockId::Height(height) = id {
+            urls.push(format!("{}/block-height/{}", self.api, height));
+        }
+        let block_hash = planned_hash(id);
+        urls.push(format!("{}/block/{}", self.api, block_hash));
+        // The number of pages depends on the transaction count of the block.
+        urls.push(format!(
+            "{}/block/{}/txs/<0, {}, {}, ... up to the transaction count>",
+            self.api,
+            block_hash,
+            MEMPOOL_PAGE_SIZE,
+            2 * MEMPOOL_PAGE_SIZE
+        ));
+        Ok(urls)
+    }
+}
+
+fn raw_block_hex_url(block_hash: &str) -> String {
+    format!("https://blockchain.info/rawblock/{}?format=hex", block_hash)
+}
+
+/// The blocks at a height on blockchain.info, read only for their hashes.
+#[derive(Deserialize)]
+struct HeightBlocks {
+    blocks: Vec<BlockHash>,
+}
+
+#[derive(Deserialize)]
+struct BlockHash {
+    hash: String,
+}
+
+/// Looks up the hash of the block at `height`, as blockchain.info serves raw
+/// blocks only by hash. Never cached, like every lookup by height.
+async fn fetch_blockchain_info_hash(
+    client: &HttpClient,
+    height: u64,
+) -> Result<String, FetchError> {
+    let url = block_height_url(&height.to_string());
+    let resp = get_json::<HeightBlocks>(client, None, &url).await?;
+    resp.blocks
+        .into_iter()
+        .next()
+        .map(|block| block.hash)
+        .ok_or_else(|| FetchError::NoBlocks(height.to_string()))
+}
+
+/// Fetches serialized blocks and decodes them locally with `rust-bitcoin`,
+/// which preserves witnesses and exact script bytes.
+pub struct RawBlockSource {
+    client: HttpClient,
+    backend: Backend,
+    network: Network,
+}
+
+impl RawBlockSource {
+    pub fn new(client: HttpClient, backend: Backend, network: Network) -> Self {
+        RawBlockSource {
+            client,
+            backend,
+            network,
+        }
+    }
+}
+
+#[async_trait]
+impl BlockSource for RawBlockSource {
+    async fn fetch(&self, id: BlockId) -> Result<Block, FetchError> {
+        match (self.backend, id) {
+            (Backend::BlockchainInfo, _) if self.network != Network::Mainnet => {
+                Err(blockchain_info_unsupported(self.network))
+            }
+            (Backend::BlockchainInfo, id) => {
+                let block_hash = match id {
+                    BlockId::Hash(hash) => hash,
+                    BlockId::Height(height) => {
+                        fetch_blockchain_info_hash(&self.client, height).await?
+                    }
+                };
+                let url = raw_block_hex_url(&block_hash);
+                let hex = text_with_retry(&self.client, &url).await?;
+                parse_raw_block_hex(&hex)
+            }
+            (Backend::Mempool, id) => {
+                let api = self.network.mempool_api();
+                let block_hash = match id {
+                    BlockId::Hash(hash) => hash,
+                    BlockId::Height(height) => {
+                        let url = format!("{}/block-height/{}", api, height);
+                        let text = text_with_retry(&self.client, &url).await?;
+                        text.trim().to_string()
+                    }
+                };
+                let url = format!("{}/block/{}/raw", api, block_hash);
+                let bytes = get_with_retry(&self.client, &url).await?;
+                parse_raw_block(&bytes)
+            }
+        }
+    }
+
+    async fn fetch_transaction(&self, txid: &str) -> Result<Transaction, FetchError> {
+        let url = match self.backend {
+            Backend::BlockchainInfo if self.network != Network::Mainnet => {
+                return Err(blockchain_info_unsupported(self.network))
+            }
+            Backend::BlockchainInfo => format!("https://blockchain.info/rawtx/{}?format=hex", txid),
+            Backend::Mempool => format!("{}/tx/{}/hex", self.network.mempool_api(), txid),
+        };
+        let hex = text_with_retry(&self.client, &url).await?;
+        parse_raw_transaction_hex(&hex)
+    }
+
+    async fn tip(&self) -> Result<Tip, FetchError> {
+        match self.backend {
+            Backend::BlockchainInfo => fetch_blockchain_info_tip(&self.client, self.network).await,
+            Backend::Mempool => fetch_mempool_tip(&self.client, &self.network.mempool_api()).await,
+        }
+    }
+
+    fn planned_urls(&self, id: &BlockId) -> Result<Vec<String>, FetchError> {
+        match (self.backend, id) {
+            (Backend::BlockchainInfo, _) if self.network != Network::Mainnet => {
+                Err(blockchain_info_unsupported(self.network))
+            }
+            (Backend::BlockchainInfo, id) => {
+                let mut urls = Vec::new();
+                if let BlockId::Height(height) = id {
+                    urls.push(block_height_url(&height.to_string()));
+                }
+                urls.push(raw_block_hex_url(&planned_hash(id)));
+                Ok(urls)
+            }
+            (Backend::Mempool, id) => {
+                let api = self.network.mempool_api();
+                let mut urls = Vec::new();
+                if let BlockId::Height(height) = id {
+                    urls.push(format!("{}/block-height/{}", api, height));
+                }
+                urls.push(format!("{}/block/{}/raw", api, planned_hash(id)));
+                Ok(urls)
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+pub enum Backend {
+    BlockchainInfo,
+    Mempool,
+}
+
+impl Backend {
+    pub fn parse(name: &str) -> Option<Backend> {
+        match name {
+            "blockchain" | "blockchain.info" => Some(Backend::BlockchainInfo),
+            "mempool" | "mempool.space" => Some(Backend::Mempool),
+            _ => None,
+        }
+    }
+
+    pub fn source(
+        self,
+        client: HttpClient,
+        network: Network,
+        cache: Option<Cache>,
+    ) -> Box<dyn BlockSource> {
+        match self {
+            Backend::BlockchainInfo => {
+                Box::new(BlockchainInfoSource::new(client, network).with_cache(cache))
+            }
+            Backend::Mempool => Box::new(MempoolSource::new(client, network).with_cache(cache)),
+        }
+    }
+
+    /// The number of requests fetching a block with `tx_count` transactions
+    /// takes, from [`Backend::raw_source`] if `raw` is set.
+    pub fn requests_per_block(self, raw: bool, tx_count: usize) -> usize {
+        match (self, raw) {
+            (Backend::BlockchainInfo, _) => 1,
+            (Backend::Mempool, true) => 2,
+            // The height lookup, the block and its pages of transactions.
+            (Backend::Mempool, false) => 2 + tx_count.div_ceil(MEMPOOL_PAGE_SIZE),
+        }
+    }
+
+    /// Like [`Backend::source`], but decodes serialized blocks locally.
+    pub fn raw_source(self, client: HttpClient, network: Network) -> Box<dyn BlockSource> {
+        Box::new(RawBlockSource::new(client, self, network))
+    }
+}
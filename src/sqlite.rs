@@ -0,0 +1,55 @@
+use bitcoin_message_finder::{AppError, Finding};
+use rusqlite::{params, Connection};
+
+/// Appends findings to a SQLite database, so results of many runs can be
+/// queried together.
+pub struct Database {
+    conn: Connection,
+}
+
+impl Database {
+    /// Opens or creates the database and its `findings` table.
+    pub fn open(path: &str) -> Result<Self, AppError> {
+        let conn = Connection::open(path)?;
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS findings (
+                 id INTEGER PRIMARY KEY,
+                 block_height INTEGER,
+                 tx_hash TEXT NOT NULL,
+                 location TEXT NOT NULL,
+                 message TEXT NOT NULL,
+                 entropy REAL NOT NULL
+             );
+             CREATE INDEX IF NOT EXISTS findings_message ON findings (message);",
+        )?;
+        Ok(Database { conn })
+    }
+
+    /// Starts a transaction; inserts until [`Database::commit`] are batched.
+    pub fn begin(&self) -> Result<(), AppError> {
+        self.conn.execute_batch("BEGIN")?;
+        Ok(())
+    }
+
+    pub fn commit(&self) -> Result<(), AppError> {
+        self.conn.execute_batch("COMMIT")?;
+        Ok(())
+    }
+
+    pub fn insert(&self, height: Option<u64>, findings: &[Finding]) -> Result<(), AppError> {
+        let mut stmt = self.conn.prepare_cached(
+            "INSERT INTO findings (block_height, tx_hash, location, message, entropy)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+        )?;
+        for finding in findings {
+            stmt.execute(params![
+                height.map(|h| h as i64),
+                finding.tx_hash,
+                finding.location.to_string(),
+                finding.message,
+                finding.entropy,
+            ])?;
+        }
+        Ok(())
+    }
+}
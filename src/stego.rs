@@ -0,0 +1,67 @@
+/// Characters that render as nothing, or as ordinary-looking space, and so
+/// can hide data in text that reads normally.
+pub fn is_invisible(c: char) -> bool {
+    matches!(
+        c,
+        '\u{00ad}'
+            | '\u{034f}'
+            | '\u{180e}'
+            | '\u{2000}'..='\u{200f}'
+            | '\u{202a}'..='\u{202f}'
+            | '\u{205f}'..='\u{2064}'
+            | '\u{3000}'
+            | '\u{feff}'
+    )
+}
+
+/// `text` without its invisible characters, as a reader sees it.
+pub fn strip_invisible(text: &str) -> String {
+    text.chars().filter(|&c| !is_invisible(c)).collect()
+}
+
+/// Reads the bit stream hidden with zero-width spaces (U+200B, a 0 bit) and
+/// zero-width non-joiners (U+200C, a 1 bit), eight bits per byte with the
+/// most significant first. Returns the bytes if they form printable ASCII.
+pub fn zero_width_bits(text: &str) -> Option<String> {
+    let bits: Vec<u8> = text
+        .chars()
+        .filter_map(|c| match c {
+            '\u{200b}' => Some(0),
+            '\u{200c}' => Some(1),
+            _ => None,
+        })
+        .collect();
+    if bits.is_empty() || !bits.len().is_multiple_of(8) {
+        return None;
+    }
+    let bytes: Vec<u8> = bits
+        .chunks(8)
+        .map(|byte| byte.iter().fold(0, |acc, bit| acc << 1 | bit))
+        .collect();
+    crate::message_from_bytes(&bytes)
+}
+
+/// Describes the invisible characters in `text`, if there are any: the
+/// message hidden in them if [`zero_width_bits`] decodes one, or else their
+/// code points.
+pub fn detect_steganography(text: &str) -> Option<String> {
+    let mut invisible: Vec<char> = text.chars().filter(|&c| is_invisible(c)).collect();
+    if invisible.is_empty() {
+        return None;
+    }
+    if let Some(hidden) = zero_width_bits(text) {
+        return Some(hidden);
+    }
+    let count = invisible.len();
+    invisible.sort_unstable();
+    invisible.dedup();
+    let code_points: Vec<String> = invisible
+        .iter()
+        .map(|&c| format!("U+{:04X}", c as u32))
+        .collect();
+    Some(format!(
+        "{} invisible characters ({})",
+        count,
+        code_points.join(", ")
+    ))
+}
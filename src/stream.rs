@@ -0,0 +1,228 @@
+use std::fmt;
+use std::io;
+
+use serde::de::{self, DeserializeSeed, Deserializer, IgnoredAny, MapAccess, SeqAccess, Visitor};
+use tokio::runtime::{Handle, RuntimeFlavor};
+
+use crate::source::ResponseBody;
+use crate::{Block, FetchError, Transaction};
+
+/// What is known of a block before its first transaction: for JSON, the
+/// fields preceding the `tx` array.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct BlockInfo {
+    pub height: Option<u64>,
+    /// The block's `n_tx`, if given.
+    pub tx_count: Option<usize>,
+}
+
+/// Receives the transactions of a block one at a time, see [`BlockStream`].
+pub trait BlockVisitor {
+    /// Called once, before the first transaction.
+    fn start(&mut self, info: &BlockInfo);
+
+    /// Called with each transaction in block order. Returning `false` skips
+    /// the rest of the block.
+    fn transaction(&mut self, tx: Transaction) -> bool;
+}
+
+/// A block whose transactions are parsed as they are visited, so a scan
+/// holds one parsed transaction at a time rather than the whole block.
+pub enum BlockStream {
+    /// A block that was parsed up front, like one decoded from raw bytes.
+    Parsed(Block),
+    /// A JSON response body, a block or a blockchain.info `{"blocks": [...]}`
+    /// list of which the first block counts. `label` names it in errors.
+    Json { label: String, body: String },
+    /// JSON like [`BlockStream::Json`], read incrementally, as from a file.
+    Reader {
+        label: String,
+        reader: Box<dyn io::Read + Send>,
+    },
+    /// JSON like [`BlockStream::Json`], parsed as the response arrives.
+    Response { label: String, body: ResponseBody },
+}
+
+impl BlockStream {
+    /// Hands every transaction of the block to `visitor`.
+    pub fn visit(self, visitor: &mut dyn BlockVisitor) -> Result<(), FetchError> {
+        match self {
+            BlockStream::Parsed(block) => {
+                visitor.start(&BlockInfo {
+                    height: block.height,
+                    tx_count: Some(block.tx.len()),
+                });
+                for tx in block.tx {
+                    if !visitor.transaction(tx) {
+                        break;
+                    }
+                }
+                Ok(())
+            }
+            BlockStream::Json { label, body } => visit_json(
+                &mut serde_json::Deserializer::from_str(&body),
+                &label,
+                visitor,
+            ),
+            BlockStream::Reader { label, reader } => visit_json(
+                &mut serde_json::Deserializer::from_reader(reader),
+                &label,
+                visitor,
+            ),
+            BlockStream::Response { label, mut body } => {
+                let visited = blocking(|| {
+                    visit_json(
+                        &mut serde_json::Deserializer::from_reader(&mut body),
+                        &label,
+                        visitor,
+                    )
+                });
+                body.finish(visited)
+            }
+        }
+    }
+}
+
+/// Runs `f`, which waits for a response body received by another task,
+/// without holding up the other tasks of a multi-threaded runtime.
+fn blocking<T>(f: impl FnOnce() -> T) -> T {
+    match Handle::try_current() {
+        Ok(handle) if handle.runtime_flavor() == RuntimeFlavor::MultiThread => {
+            tokio::task::block_in_place(f)
+        }
+        _ => f(),
+    }
+}
+
+fn visit_json<'de, R: serde_json::de::Read<'de>>(
+    de: &mut serde_json::Deserializer<R>,
+    label: &str,
+    visitor: &mut dyn BlockVisitor,
+) -> Result<(), FetchError> {
+    let mut found = false;
+    BlockMap {
+        visitor,
+        found: &mut found,
+    }
+    .deserialize(&mut *de)?;
+    de.end()?;
+    if !found {
+        return Err(FetchError::NoBlocks(label.to_string()));
+    }
+    Ok(())
+}
+
+/// A block object, or the object listing blocks around it.
+struct BlockMap<'a> {
+    visitor: &'a mut dyn BlockVisitor,
+    found: &'a mut bool,
+}
+
+impl<'de> DeserializeSeed<'de> for BlockMap<'_> {
+    type Value = ();
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<(), D::Error> {
+        deserializer.deserialize_map(self)
+    }
+}
+
+impl<'de> Visitor<'de> for BlockMap<'_> {
+    type Value = ();
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a block or a list of blocks")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<(), A::Error> {
+        let mut info = BlockInfo::default();
+        let mut listed = false;
+        let mut block = false;
+        while let Some(key) = map.next_key::<String>()? {
+            match key.as_str() {
+                "blocks" => {
+                    map.next_value_seed(Blocks {
+                        visitor: &mut *self.visitor,
+                        found: &mut *self.found,
+                    })?;
+                    listed = true;
+                }
+                "height" => info.height = map.next_value()?,
+                "n_tx" => info.tx_count = map.next_value()?,
+                "tx" => {
+                    self.visitor.start(&info);
+                    map.next_value_seed(Transactions(&mut *self.visitor))?;
+                    block = true;
+                }
+                _ => {
+                    map.next_value::<IgnoredAny>()?;
+                }
+            }
+        }
+        if !listed && !block {
+            return Err(de::Error::missing_field("tx"));
+        }
+        *self.found |= block;
+        Ok(())
+    }
+}
+
+/// The `blocks` list, of which only the first block is visited.
+struct Blocks<'a> {
+    visitor: &'a mut dyn BlockVisitor,
+    found: &'a mut bool,
+}
+
+impl<'de> DeserializeSeed<'de> for Blocks<'_> {
+    type Value = ();
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<(), D::Error> {
+        deserializer.deserialize_seq(self)
+    }
+}
+
+impl<'de> Visitor<'de> for Blocks<'_> {
+    type Value = ();
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a list of blocks")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<(), A::Error> {
+        seq.next_element_seed(BlockMap {
+            visitor: self.visitor,
+            found: self.found,
+        })?;
+        while seq.next_element::<IgnoredAny>()?.is_some() {}
+        Ok(())
+    }
+}
+
+/// The `tx` array, each element handed to the visitor as soon as it parses.
+struct Transactions<'a>(&'a mut dyn BlockVisitor);
+
+impl<'de> DeserializeSeed<'de> for Transactions<'_> {
+    type Value = ();
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<(), D::Error> {
+        deserializer.deserialize_seq(self)
+    }
+}
+
+impl<'de> Visitor<'de> for Transactions<'_> {
+    type Value = ();
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a list of transactions")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<(), A::Error> {
+        while let Some(tx) = seq.next_element::<Transaction>()? {
+            if !self.0.transaction(tx) {
+                // The rest of the array still has to be read past.
+                while seq.next_element::<IgnoredAny>()?.is_some() {}
+                break;
+            }
+        }
+        Ok(())
+    }
+}
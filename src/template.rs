@@ -0,0 +1,163 @@
+use std::fmt::Write;
+
+use bitcoin_message_finder::Finding;
+
+/// A value of a finding, or of where it was found, that a template can use.
+#[derive(Clone, Copy, Debug)]
+enum Placeholder {
+    Height,
+    TxIndex,
+    TxHash,
+    Location,
+    Index,
+    ItemIndex,
+    Message,
+    RawHex,
+    Decoder,
+    Entropy,
+    EnglishScore,
+    Kind,
+    FileType,
+    Protocol,
+    Pool,
+    Address,
+    SourceFile,
+    Annotations,
+}
+
+/// The names of the placeholders, mostly those of the JSON fields.
+const PLACEHOLDERS: &[(&str, Placeholder)] = &[
+    ("height", Placeholder::Height),
+    ("tx_index", Placeholder::TxIndex),
+    ("tx_hash", Placeholder::TxHash),
+    ("location", Placeholder::Location),
+    ("index", Placeholder::Index),
+    ("item_index", Placeholder::ItemIndex),
+    ("message", Placeholder::Message),
+    ("raw_hex", Placeholder::RawHex),
+    ("decoder", Placeholder::Decoder),
+    ("entropy", Placeholder::Entropy),
+    ("english_score", Placeholder::EnglishScore),
+    ("kind", Placeholder::Kind),
+    ("file_type", Placeholder::FileType),
+    ("protocol", Placeholder::Protocol),
+    ("pool", Placeholder::Pool),
+    ("address", Placeholder::Address),
+    ("source_file", Placeholder::SourceFile),
+    ("annotations", Placeholder::Annotations),
+];
+
+#[derive(Clone, Debug)]
+enum Part {
+    Literal(String),
+    Field(Placeholder),
+}
+
+/// A `--template` for text findings, parsed once: literal text with
+/// `{placeholder}`s, where `{{` and `}}` stand for braces.
+#[derive(Clone, Debug)]
+pub struct Template {
+    parts: Vec<Part>,
+}
+
+impl Template {
+    pub fn parse(template: &str) -> Result<Self, String> {
+        let mut parts = Vec::new();
+        let mut literal = String::new();
+        let mut chars = template.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                '{' if chars.as_str().starts_with('{') => {
+                    chars.next();
+                    literal.push('{');
+                }
+                '}' if chars.as_str().starts_with('}') => {
+                    chars.next();
+                    literal.push('}');
+                }
+                '{' => {
+                    let rest = chars.as_str();
+                    let end = rest
+                        .find('}')
+                        .ok_or_else(|| "unclosed '{', use '{{' for a brace".to_string())?;
+                    let name = &rest[..end];
+                    let &(_, field) = PLACEHOLDERS
+                        .iter()
+                        .find(|(placeholder, _)| *placeholder == name)
+                        .ok_or_else(|| {
+                            let names: Vec<&str> =
+                                PLACEHOLDERS.iter().map(|(name, _)| *name).collect();
+                            format!(
+                                "unknown placeholder {{{}}}, expected one of {}",
+                                name,
+                                names.join(", ")
+                            )
+                        })?;
+                    if !literal.is_empty() {
+                        parts.push(Part::Literal(std::mem::take(&mut literal)));
+                    }
+                    parts.push(Part::Field(field));
+                    chars = rest[end + 1..].chars();
+                }
+                '}' => return Err("unmatched '}', use '}}' for a brace".to_string()),
+                c => literal.push(c),
+            }
+        }
+        if !literal.is_empty() {
+            parts.push(Part::Literal(literal));
+        }
+        Ok(Template { parts })
+    }
+
+    /// Fills in the placeholders for `finding`, found in the block at
+    /// `height` as transaction `tx_index`. Values that are not known or not
+    /// set are left empty.
+    pub fn render(
+        &self,
+        finding: &Finding,
+        height: Option<u64>,
+        tx_index: Option<usize>,
+    ) -> String {
+        let mut line = String::new();
+        for part in &self.parts {
+            let field = match part {
+                Part::Literal(text) => {
+                    line.push_str(text);
+                    continue;
+                }
+                Part::Field(field) => *field,
+            };
+            // Writing to a String cannot fail.
+            let _ = match field {
+                Placeholder::Height => write_some(&mut line, height),
+                Placeholder::TxIndex => write_some(&mut line, tx_index),
+                Placeholder::TxHash => write!(line, "{}", finding.tx_hash),
+                Placeholder::Location => write!(line, "{}", finding.location),
+                Placeholder::Index => write!(line, "{}", finding.vin_or_vout_index),
+                Placeholder::ItemIndex => write_some(&mut line, finding.item_index),
+                Placeholder::Message => write!(line, "{}", finding.message),
+                Placeholder::RawHex => write!(line, "{}", finding.raw_hex),
+                Placeholder::Decoder => write_some(&mut line, finding.decoder.as_ref()),
+                Placeholder::Entropy => write!(line, "{:.2}", finding.entropy),
+                Placeholder::EnglishScore => write!(line, "{:.2}", finding.english_score),
+                Placeholder::Kind => write_some(&mut line, finding.kind),
+                Placeholder::FileType => write_some(&mut line, finding.file_type.as_ref()),
+                Placeholder::Protocol => write_some(&mut line, finding.protocol.as_ref()),
+                Placeholder::Pool => write_some(&mut line, finding.pool.as_ref()),
+                Placeholder::Address => write_some(&mut line, finding.address.as_ref()),
+                Placeholder::SourceFile => write_some(&mut line, finding.source_file.as_ref()),
+                Placeholder::Annotations => {
+                    write!(line, "{}", finding.annotations().trim_start())
+                }
+            };
+        }
+        line
+    }
+}
+
+fn write_some(line: &mut String, value: Option<impl std::fmt::Display>) -> std::fmt::Result {
+    match value {
+        Some(value) => write!(line, "{}", value),
+        None => Ok(()),
+    }
+}
@@ -0,0 +1,107 @@
+use std::time::Duration;
+
+use bitcoin_message_finder::Finding;
+use reqwest::Client;
+use serde::Serialize;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+
+const MAX_RETRIES: u32 = 3;
+const RETRY_BASE_DELAY: Duration = Duration::from_millis(500);
+
+/// A finding as POSTed to the webhook.
+#[derive(Serialize)]
+pub struct Event {
+    pub block_height: Option<u64>,
+    #[serde(flatten)]
+    pub finding: Finding,
+}
+
+/// Delivers findings to a webhook in the background, so a slow endpoint
+/// doesn't hold up the scan.
+pub struct Webhook {
+    task: JoinHandle<()>,
+}
+
+impl Webhook {
+    /// Starts delivering the events sent on the returned channel to `url`.
+    /// Everything queued while a request is in flight goes out as one JSON
+    /// array, which in practice is one batch per block. An attempt that gets
+    /// no response within `timeout` fails like any other.
+    pub fn spawn(
+        client: Client,
+        timeout: Duration,
+        url: String,
+    ) -> (Webhook, mpsc::UnboundedSender<Event>) {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        let task = tokio::spawn(deliver(client, timeout, url, receiver));
+        (Webhook { task }, sender)
+    }
+
+    /// Waits until every event has been delivered, or given up on. The
+    /// sender must be dropped first.
+    pub async fn finish(self) {
+        let _ = self.task.await;
+    }
+}
+
+async fn deliver(
+    client: Client,
+    timeout: Duration,
+    url: String,
+    mut receiver: mpsc::UnboundedReceiver<Event>,
+) {
+    // Events of failed requests are kept and sent again with the next batch.
+    let mut pending = Vec::new();
+    while let Some(event) = receiver.recv().await {
+        pending.push(event);
+        while let Ok(event) = receiver.try_recv() {
+            pending.push(event);
+        }
+        match post_with_retry(&client, timeout, &url, &pending).await {
+            Ok(()) => pending.clear(),
+            Err(e) => eprintln!(
+                "Webhook {} failed, keeping {} finding(s) for the next attempt: {}",
+                url,
+                pending.len(),
+                e
+            ),
+        }
+    }
+    if !pending.is_empty()
+        && post_with_retry(&client, timeout, &url, &pending)
+            .await
+            .is_err()
+    {
+        eprintln!(
+            "Webhook {} failed, {} finding(s) were not delivered.",
+            url,
+            pending.len()
+        );
+    }
+}
+
+async fn post_with_retry(
+    client: &Client,
+    timeout: Duration,
+    url: &str,
+    events: &[Event],
+) -> reqwest::Result<()> {
+    let mut attempt = 0;
+    loop {
+        let result = client
+            .post(url)
+            .timeout(timeout)
+            .json(events)
+            .send()
+            .await
+            .and_then(|resp| resp.error_for_status());
+        match result {
+            Ok(_) => return Ok(()),
+            Err(e) if attempt >= MAX_RETRIES => return Err(e),
+            Err(_) => {}
+        }
+        tokio::time::sleep(RETRY_BASE_DELAY * 2u32.pow(attempt)).await;
+        attempt += 1;
+    }
+}
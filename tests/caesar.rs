@@ -0,0 +1,32 @@
+use bitcoin_message_finder::{caesar_brute_force, caesar_shift};
+
+const HEADLINE: &str = "The Times 03/Jan/2009 Chancellor on brink of second bailout for banks";
+
+#[test]
+fn shift_keeps_case_and_other_characters() {
+    assert_eq!(caesar_shift("Hello, World! 42", 3), "Khoor, Zruog! 42");
+    assert_eq!(caesar_shift("xyz XYZ", 3), "abc ABC");
+    assert_eq!(caesar_shift(&caesar_shift(HEADLINE, 13), 13), HEADLINE);
+}
+
+#[test]
+fn rot13_headline() {
+    let rot13 = "Gur Gvzrf 03/Wna/2009 Punapryybe ba oevax bs frpbaq onvybhg sbe onaxf";
+    assert_eq!(caesar_shift(HEADLINE, 13), rot13);
+    assert_eq!(caesar_brute_force(rot13), Some((13, HEADLINE.to_string())));
+}
+
+#[test]
+fn other_shifts() {
+    let shifted = caesar_shift("hello this is the message", 5);
+    assert_eq!(
+        caesar_brute_force(&shifted),
+        Some((5, "hello this is the message".to_string()))
+    );
+}
+
+#[test]
+fn english_and_gibberish_are_left_alone() {
+    assert_eq!(caesar_brute_force(HEADLINE), None);
+    assert_eq!(caesar_brute_force("xq7Zk2pLw9vR"), None);
+}
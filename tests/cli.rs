@@ -0,0 +1,406 @@
+use std::process::{Command, Output};
+
+const GENESIS: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/genesis.json");
+/// A coinbase with three printable runs.
+const RUNS: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/runs.json");
+/// Two transactions with three identical OP_RETURN outputs between them.
+const SPAM: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/spam.json");
+const GENESIS_TX: &str = "4a5e1e4baab89f3a32518a88c31bc87f618f76673e2cc77ab2127b7afdeda33b";
+/// A PNG header in an OP_RETURN output.
+const PNG: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/png.json");
+
+/// Runs the binary with `args` and the given `BMF_*` variables, and no other
+/// `BMF_*` settings from the calling environment.
+fn run(args: &[&str], env: &[(&str, &str)]) -> Output {
+    let mut command = Command::new(env!("CARGO_BIN_EXE_bitcoin_message_finder"));
+    command.args(args).env("NO_COLOR", "1");
+    for name in ["BMF_BACKEND", "BMF_NETWORK", "BMF_MIN_LENGTH"] {
+        command.env_remove(name);
+    }
+    command.envs(env.iter().copied());
+    command.output().expect("binary runs")
+}
+
+fn stdout(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stdout).into_owned()
+}
+
+#[test]
+fn backend_and_network_default_to_blockchain_info_mainnet() {
+    let output = run(&["block", "5", "--dry-run"], &[]);
+    assert!(output.status.success());
+    assert_eq!(
+        stdout(&output),
+        "https://blockchain.info/block-height/5?format=json\n"
+    );
+}
+
+#[test]
+fn environment_sets_backend_and_network() {
+    let env = [("BMF_BACKEND", "mempool"), ("BMF_NETWORK", "signet")];
+    let output = run(&["block", "5", "--dry-run"], &env);
+    assert!(output.status.success());
+    assert!(stdout(&output).starts_with("https://mempool.space/signet/api/block-height/5\n"));
+}
+
+#[test]
+fn flags_override_environment() {
+    let env = [("BMF_BACKEND", "mempool"), ("BMF_NETWORK", "signet")];
+    let args = [
+        "block",
+        "5",
+        "--dry-run",
+        "--backend",
+        "blockchain",
+        "--network",
+        "mainnet",
+    ];
+    let output = run(&args, &env);
+    assert!(output.status.success());
+    assert_eq!(
+        stdout(&output),
+        "https://blockchain.info/block-height/5?format=json\n"
+    );
+}
+
+#[test]
+fn invalid_environment_value_is_reported() {
+    let output = run(&["block", "5", "--dry-run"], &[("BMF_BACKEND", "bogus")]);
+    assert_eq!(output.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("BMF_BACKEND"));
+
+    // Not even looked at when the flag is given.
+    let output = run(
+        &["block", "5", "--dry-run", "--backend", "mempool"],
+        &[("BMF_BACKEND", "bogus")],
+    );
+    assert!(output.status.success());
+}
+
+#[test]
+fn min_length_from_environment_and_flag() {
+    let args = ["file", GENESIS, "--strings", "--quiet"];
+    // The genesis headline run is 71 characters long.
+    assert_eq!(run(&args, &[]).status.code(), Some(0));
+    assert_eq!(
+        run(&args, &[("BMF_MIN_LENGTH", "80")]).status.code(),
+        Some(1)
+    );
+
+    let args = [
+        "file",
+        GENESIS,
+        "--strings",
+        "--quiet",
+        "--min-length",
+        "10",
+    ];
+    let output = run(&args, &[("BMF_MIN_LENGTH", "80")]);
+    assert_eq!(output.status.code(), Some(0));
+    assert!(stdout(&output).contains("Chancellor on brink of second bailout"));
+}
+
+#[test]
+fn range_dry_run_lists_every_height() {
+    let output = run(&["range", "5", "7", "--dry-run"], &[]);
+    assert!(output.status.success());
+    assert_eq!(stdout(&output).lines().count(), 3);
+}
+
+#[test]
+fn heights_dry_run_lists_each_height_once() {
+    let output = run(&["heights", "250,100", "1000", "100", "--dry-run"], &[]);
+    assert!(output.status.success());
+    assert_eq!(
+        stdout(&output),
+        "https://blockchain.info/block-height/100?format=json\n\
+         https://blockchain.info/block-height/250?format=json\n\
+         https://blockchain.info/block-height/1000?format=json\n"
+    );
+    assert_eq!(run(&["heights", "--dry-run"], &[]).status.code(), Some(2));
+    assert_eq!(run(&["heights", "5,x"], &[]).status.code(), Some(2));
+}
+
+#[test]
+fn raw_blocks_by_height_look_up_the_hash_first() {
+    let output = run(&["block", "5", "--raw", "--dry-run"], &[]);
+    assert!(output.status.success());
+    assert_eq!(
+        stdout(&output),
+        "https://blockchain.info/block-height/5?format=json\n\
+         https://blockchain.info/rawblock/<hash of block 5>?format=hex\n"
+    );
+}
+
+#[test]
+fn since_timestamp_replaces_the_start_height() {
+    let since = |args: &[&str]| {
+        let mut all = vec!["range", "--since-timestamp"];
+        all.extend_from_slice(args);
+        run(&all, &[])
+    };
+    let output = since(&["2024-01-01", "--dry-run"]);
+    assert_eq!(output.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("--dry-run needs"));
+    assert_eq!(since(&["2024-01-01", "5", "6"]).status.code(), Some(2));
+    assert_eq!(since(&["first of may"]).status.code(), Some(2));
+    assert_eq!(run(&["range", "5"], &[]).status.code(), Some(2));
+}
+
+#[test]
+fn profile_takes_an_optional_count() {
+    let output = run(&["heights", "100", "--profile", "200", "--dry-run"], &[]);
+    assert!(output.status.success());
+    assert_eq!(stdout(&output).lines().count(), 1);
+    let output = run(&["range", "5", "6", "--profile", "--dry-run"], &[]);
+    assert!(output.status.success());
+    assert_eq!(stdout(&output).lines().count(), 2);
+}
+
+#[test]
+fn subcommand_arguments_are_validated() {
+    assert_eq!(run(&[], &[]).status.code(), Some(2));
+    assert_eq!(run(&["tx"], &[]).status.code(), Some(2));
+    assert_eq!(run(&["tx", "ab", "--stdin"], &[]).status.code(), Some(2));
+    assert_eq!(
+        run(&["range", "5", "7", "--concurrency", "0"], &[])
+            .status
+            .code(),
+        Some(2)
+    );
+}
+
+#[test]
+fn limit_per_tx_notes_the_rest() {
+    let output = run(
+        &["file", RUNS, "--all", "--strings", "--limit-per-tx", "2"],
+        &[],
+    );
+    assert_eq!(output.status.code(), Some(0));
+    let text = stdout(&output);
+    assert!(text.contains("Second message"));
+    assert!(!text.contains("Third message"));
+    assert!(text.contains(": ... and 1 more\n"));
+}
+
+#[test]
+fn exclude_removes_locations_and_conflicts_with_only() {
+    let args = ["file", GENESIS, "--strings", "--quiet"];
+    let excluded = [&args[..], &["--exclude", "coinbase"]].concat();
+    assert_eq!(run(&excluded, &[]).status.code(), Some(1));
+    let kept = [&args[..], &["--exclude", "scriptpubkey,witness"]].concat();
+    assert_eq!(run(&kept, &[]).status.code(), Some(0));
+
+    let both = [&excluded[..], &["--only", "witness"]].concat();
+    let output = run(&both, &[]);
+    assert_eq!(output.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("cannot be used with"));
+}
+
+#[test]
+fn clusters_count_identical_scripts() {
+    let output = run(&["file", SPAM, "--all", "--clusters"], &[]);
+    assert_eq!(output.status.code(), Some(0));
+    let text = stdout(&output);
+    assert!(text.contains("  Duplicate scripts:\n    3x OP_RETURN: mint token-ABC\n"));
+    assert!(!text.contains("x OP_RETURN: just a note"));
+
+    // Several messages from one script count once.
+    let output = run(&["file", RUNS, "--all", "--strings", "--clusters"], &[]);
+    assert!(!stdout(&output).contains("Duplicate scripts"));
+}
+
+#[test]
+fn json_summary_is_part_of_the_one_document() {
+    let args = ["file", GENESIS, "--all", "--strings", "--format", "json"];
+    let output = run(&[&args[..], &["--summary"]].concat(), &[]);
+    assert_eq!(output.status.code(), Some(0));
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert!(!report["findings"].as_array().unwrap().is_empty());
+    assert!(report["summary"].is_object());
+}
+
+#[test]
+fn print_schema_describes_json_findings() {
+    let output = run(&["--print-schema"], &[]);
+    assert_eq!(output.status.code(), Some(0));
+    let schema: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(schema["title"], "Finding");
+    let locations = schema["$defs"]["MessageLocation"].to_string();
+    assert!(locations.contains("\"op_return\"") && locations.contains("\"coinbase_tag\""));
+    assert!(schema["$defs"]["MessageKind"]
+        .to_string()
+        .contains("\"url\""));
+
+    let output = run(
+        &["file", GENESIS, "--all", "--strings", "--format", "json"],
+        &[],
+    );
+    let findings: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert!(!findings.as_array().unwrap().is_empty());
+    let properties = schema["properties"].as_object().unwrap();
+    for finding in findings.as_array().unwrap() {
+        for field in finding.as_object().unwrap().keys() {
+            assert!(
+                properties.contains_key(field),
+                "{} is not in the schema",
+                field
+            );
+        }
+    }
+    for field in schema["required"].as_array().unwrap() {
+        assert!(findings[0].get(field.as_str().unwrap()).is_some());
+    }
+}
+
+#[test]
+fn first_only_stops_at_the_first_finding() {
+    let output = run(&["file", SPAM, "--all", "--first-only"], &[]);
+    assert_eq!(output.status.code(), Some(0));
+    let text = stdout(&output);
+    assert_eq!(text.matches("OP_RETURN:").count(), 1);
+    assert!(!text.contains("Transaction 1:"));
+
+    let output = run(&["file", RUNS, "--all", "--strings", "--first-only"], &[]);
+    let text = stdout(&output);
+    assert!(text.contains("First message"));
+    assert!(!text.contains("Second message") && !text.contains("more"));
+}
+
+#[test]
+fn dump_dir_saves_embedded_files_without_overwriting() {
+    let dir = std::env::temp_dir().join(format!("bmf-dump-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    let dir_arg = dir.to_str().unwrap();
+    for _ in 0..2 {
+        let output = run(&["file", PNG, "--all", "--dump-dir", dir_arg], &[]);
+        assert_eq!(output.status.code(), Some(0));
+    }
+    let stem = format!("{}_op_return_0", "3".repeat(64));
+    let first = std::fs::read(dir.join(format!("{}.png", stem))).unwrap();
+    assert!(first.starts_with(b"\x89PNG\r\n\x1a\n"));
+    assert_eq!(first.len(), 16);
+    assert!(dir.join(format!("{}_1.png", stem)).exists());
+
+    // Text findings are only saved with --dump-all.
+    run(
+        &["file", GENESIS, "--all", "--strings", "--dump-dir", dir_arg],
+        &[],
+    );
+    assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 2);
+    run(
+        &[
+            "file",
+            GENESIS,
+            "--all",
+            "--strings",
+            "--dump-dir",
+            dir_arg,
+            "--dump-all",
+        ],
+        &[],
+    );
+    assert!(dir.join(format!("{}_coinbase_0.bin", GENESIS_TX)).exists());
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn dir_scans_block_files_in_name_order_and_skips_others() {
+    let dir = std::env::temp_dir().join(format!("bmf-dir-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::copy(RUNS, dir.join("b_runs.json")).unwrap();
+    std::fs::copy(GENESIS, dir.join("a_genesis.json")).unwrap();
+    std::fs::write(dir.join("c_bad.json"), r#"{"not": "a block"}"#).unwrap();
+    std::fs::write(dir.join("notes.txt"), "not json").unwrap();
+
+    let output = run(
+        &[
+            "dir",
+            dir.to_str().unwrap(),
+            "--strings",
+            "--format",
+            "ndjson",
+        ],
+        &[],
+    );
+    assert_eq!(output.status.code(), Some(0));
+    let files: Vec<String> = stdout(&output)
+        .lines()
+        .map(|line| {
+            let finding: serde_json::Value = serde_json::from_str(line).unwrap();
+            finding["source_file"].as_str().unwrap().to_string()
+        })
+        .collect();
+    assert_eq!(
+        files,
+        [
+            "a_genesis.json",
+            "b_runs.json",
+            "b_runs.json",
+            "b_runs.json"
+        ]
+    );
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("skipping") && stderr.contains("c_bad.json"));
+    assert!(!stderr.contains("notes.txt"));
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn template_shapes_each_text_finding() {
+    let output = run(
+        &[
+            "file",
+            RUNS,
+            "--all",
+            "--strings",
+            "--quiet",
+            "--template",
+            "{height}\t{location}\t{{{message}}}",
+        ],
+        &[],
+    );
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(
+        stdout(&output),
+        "1\tCoinbase\t{First message}\n1\tCoinbase\t{Second message}\n1\tCoinbase\t{Third message}\n"
+    );
+
+    let output = run(&["file", RUNS, "--template", "{message} {txid}"], &[]);
+    assert_eq!(output.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("unknown placeholder {txid}"));
+    let output = run(&["file", RUNS, "--template", "{message"], &[]);
+    assert_eq!(output.status.code(), Some(2));
+}
+
+#[test]
+fn unresponsive_webhook_does_not_hold_up_the_exit() {
+    // Accepts connections but never answers them.
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}/", listener.local_addr().unwrap());
+    std::thread::spawn(move || {
+        let mut held = Vec::new();
+        while let Ok((socket, _)) = listener.accept() {
+            held.push(socket);
+        }
+    });
+
+    let mut child = Command::new(env!("CARGO_BIN_EXE_bitcoin_message_finder"))
+        .args(["file", PNG, "--webhook", &url, "--timeout-secs", "1"])
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::piped())
+        .spawn()
+        .unwrap();
+    let started = std::time::Instant::now();
+    while child.try_wait().unwrap().is_none() {
+        if started.elapsed() > std::time::Duration::from_secs(60) {
+            child.kill().unwrap();
+            panic!("still waiting for the webhook");
+        }
+        std::thread::sleep(std::time::Duration::from_millis(100));
+    }
+    let output = child.wait_with_output().unwrap();
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("were not delivered"), "{}", stderr);
+}
@@ -0,0 +1,44 @@
+use std::io::Write;
+
+use bitcoin_message_finder::compress::{decompress, MAX_DECOMPRESSED_SIZE};
+use flate2::write::{GzEncoder, ZlibEncoder};
+use flate2::Compression;
+
+fn gzip(data: &[u8]) -> Vec<u8> {
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::best());
+    encoder.write_all(data).unwrap();
+    encoder.finish().unwrap()
+}
+
+fn zlib(data: &[u8]) -> Vec<u8> {
+    let mut encoder = ZlibEncoder::new(Vec::new(), Compression::best());
+    encoder.write_all(data).unwrap();
+    encoder.finish().unwrap()
+}
+
+#[test]
+fn gzip_and_zlib_round_trip() {
+    let text = b"inflated again";
+    assert_eq!(decompress(&gzip(text), "gzip").as_deref(), Some(&text[..]));
+    assert_eq!(decompress(&zlib(text), "zlib").as_deref(), Some(&text[..]));
+}
+
+#[test]
+fn other_kinds_and_corrupt_data_are_ignored() {
+    assert_eq!(decompress(&gzip(b"text"), "png"), None);
+    assert_eq!(decompress(b"\x1f\x8b\x08\x00not gzip", "gzip"), None);
+}
+
+#[test]
+fn output_above_the_cap_is_rejected() {
+    let cap = MAX_DECOMPRESSED_SIZE as usize;
+    let at_cap = gzip(&vec![0; cap]);
+    assert_eq!(
+        decompress(&at_cap, "gzip").map(|output| output.len()),
+        Some(cap)
+    );
+
+    let bomb = zlib(&vec![0; cap + 1]);
+    assert!(bomb.len() < 4096);
+    assert_eq!(decompress(&bomb, "zlib"), None);
+}
@@ -0,0 +1,24 @@
+use bitcoin_message_finder::{hexdump, HEXDUMP_MAX_BYTES};
+
+#[test]
+fn full_and_partial_lines() {
+    let bytes = b"\x89PNG\r\n\x1a\n\x00\x00\x00\rIHDRabc";
+    assert_eq!(
+        hexdump(bytes),
+        "00000000  89 50 4e 47 0d 0a 1a 0a  00 00 00 0d 49 48 44 52  |.PNG........IHDR|\n\
+         00000010  61 62 63                                          |abc|\n"
+    );
+}
+
+#[test]
+fn empty_input() {
+    assert_eq!(hexdump(&[]), "");
+}
+
+#[test]
+fn long_payloads_are_capped() {
+    let bytes = vec![b'A'; HEXDUMP_MAX_BYTES + 10];
+    let dump = hexdump(&bytes);
+    assert_eq!(dump.lines().count(), HEXDUMP_MAX_BYTES / 16 + 1);
+    assert!(dump.ends_with("|AAAAAAAAAAAAAAAA|\n... 10 more bytes\n"));
+}
@@ -0,0 +1,84 @@
+use bitcoin_message_finder::{
+    check_transaction_with_options, extract_inscription, InscriptionSpan, MessageLocation,
+    ScanOptions, Transaction, Vin,
+};
+
+fn push(data: &[u8]) -> String {
+    format!("{:02x}{}", data.len(), hex::encode(data))
+}
+
+/// `OP_FALSE OP_IF "ord" 1 "text/plain" OP_0`, the start of a text envelope.
+fn text_header() -> String {
+    format!(
+        "0063{}{}{}00",
+        push(b"ord"),
+        push(&[1]),
+        push(b"text/plain")
+    )
+}
+
+fn witness_tx(witness: Vec<String>) -> Transaction {
+    Transaction {
+        hash: "00ff".to_string(),
+        hex: None,
+        vin: vec![Vin {
+            coinbase: None,
+            txid: Some("11".repeat(32)),
+            vout: Some(0),
+            script_sig: None,
+            sequence: None,
+            txinwitness: witness,
+        }],
+        vout: Vec::new(),
+    }
+}
+
+#[test]
+fn body_pushes_are_counted() {
+    let script = format!(
+        "{}{}{}68",
+        text_header(),
+        push(b"Hello, "),
+        push(b"ordinals!")
+    );
+    let inscription = extract_inscription(&["aa".repeat(64), script]).unwrap();
+    assert!(inscription.is_text());
+    assert_eq!(inscription.body, b"Hello, ordinals!");
+    assert_eq!(inscription.witness_items, vec![1]);
+    assert_eq!(inscription.pushes, 2);
+}
+
+#[test]
+fn unclosed_envelopes_continue_in_the_next_item() {
+    let first = format!("{}{}", text_header(), push(b"split across "));
+    let second = format!("{}68", push(b"witness items"));
+    let inscription = extract_inscription(&[first, second]).unwrap();
+    assert_eq!(inscription.body, b"split across witness items");
+    assert_eq!(inscription.witness_items, vec![0, 1]);
+    assert_eq!(inscription.pushes, 2);
+}
+
+#[test]
+fn findings_record_the_span() {
+    let first = format!("{}{}", text_header(), push(b"split across "));
+    let second = format!("{}{}68", push(b"three "), push(b"pushes"));
+    let tx = witness_tx(vec![first, second]);
+    let options = ScanOptions {
+        locations: Some(vec![MessageLocation::Inscription]),
+        ..ScanOptions::default()
+    };
+    let findings = check_transaction_with_options(&tx, &options);
+    assert_eq!(findings.len(), 1);
+    assert_eq!(findings[0].message, "split across three pushes");
+    assert_eq!(
+        findings[0].inscription_span,
+        Some(InscriptionSpan {
+            length: 25,
+            witness_items: vec![0, 1],
+            pushes: 3,
+        })
+    );
+    assert!(findings[0]
+        .annotations()
+        .contains("[25 bytes in 3 pushes, witness items 0, 1]"));
+}
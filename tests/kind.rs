@@ -0,0 +1,49 @@
+use bitcoin_message_finder::{classify_message, looks_like_hash, MessageKind};
+
+#[test]
+fn urls_without_trailing_punctuation() {
+    assert_eq!(
+        classify_message("Visit http://bitcoin.org/en/, it's great"),
+        Some((MessageKind::Url, "http://bitcoin.org/en/"))
+    );
+    assert_eq!(
+        classify_message("(HTTPS://Example.com/a?b=c)"),
+        Some((MessageKind::Url, "HTTPS://Example.com/a?b=c"))
+    );
+}
+
+#[test]
+fn email_addresses() {
+    assert_eq!(
+        classify_message("contact: satoshi@gmx.com."),
+        Some((MessageKind::Email, "satoshi@gmx.com"))
+    );
+    // A URL wins over an address in the same message.
+    assert_eq!(
+        classify_message("me@example.org https://example.org"),
+        Some((MessageKind::Url, "https://example.org"))
+    );
+}
+
+#[test]
+fn plain_text_has_no_kind() {
+    assert_eq!(classify_message("Chancellor on brink"), None);
+    assert_eq!(classify_message("user@localhost"), None);
+    assert_eq!(classify_message("ftp://example.com"), None);
+}
+
+/// The genesis block's hash, in the byte order it is serialized in.
+const GENESIS_HASH: &str = "6fe28c0ab6f1b372c1a6a246ae63f74f931e8365e15a089c68d6190000000000";
+
+#[test]
+fn hashes_are_told_from_text_and_padding() {
+    assert!(looks_like_hash(&hex::decode(GENESIS_HASH).unwrap()));
+    assert!(!looks_like_hash(&[0; 32]));
+    assert!(!looks_like_hash(b"thirty-two printable characters!"));
+    assert!(!looks_like_hash(&hex::decode(&GENESIS_HASH[..62]).unwrap()));
+    assert_eq!(
+        classify_message(GENESIS_HASH),
+        Some((MessageKind::HashReference, GENESIS_HASH))
+    );
+    assert_eq!(classify_message(&"ab".repeat(32)), None);
+}
@@ -0,0 +1,25 @@
+use std::time::{Duration, Instant};
+
+use bitcoin_message_finder::RateLimiter;
+
+#[test]
+fn rate_must_be_positive() {
+    assert!(RateLimiter::new(0.0).is_none());
+    assert!(RateLimiter::new(-1.0).is_none());
+    assert!(RateLimiter::new(f64::NAN).is_none());
+    assert!(RateLimiter::new(0.5).is_some());
+}
+
+#[tokio::test]
+async fn requests_beyond_the_burst_wait() {
+    let limiter = RateLimiter::new(20.0).unwrap();
+    let start = Instant::now();
+    for _ in 0..20 {
+        limiter.acquire().await;
+    }
+    assert!(start.elapsed() < Duration::from_millis(100));
+    for _ in 0..5 {
+        limiter.acquire().await;
+    }
+    assert!(start.elapsed() >= Duration::from_millis(200));
+}
@@ -0,0 +1,754 @@
+use bitcoin_message_finder::{
+    check_block_transaction, check_transaction_for_messages, check_transaction_with_options,
+    detect_file_type, english_score, extract_hidden_message, identify_protocol, scan_bytes,
+    script_address, shannon_entropy, xor_brute_force, Context, Decoder, DecoderRegistry, Encoding,
+    ExtractMode, Finding, FindingFilter, MessageKind, MessageLocation, Network, ScanOptions,
+    ScriptPubKey, ScriptSig, Transaction, Utf16Le, Vin, Vout, XorKey,
+};
+
+use std::io::Write;
+
+use flate2::write::{GzEncoder, ZlibEncoder};
+use flate2::Compression;
+
+const TX_HASH: &str = "00ff";
+
+fn transaction(vin: Vec<Vin>, vout: Vec<Vout>) -> Transaction {
+    Transaction {
+        hash: TX_HASH.to_string(),
+        hex: None,
+        vin,
+        vout,
+    }
+}
+
+fn coinbase_vin(coinbase: &str) -> Vin {
+    Vin {
+        coinbase: Some(coinbase.to_string()),
+        txid: None,
+        vout: None,
+        script_sig: None,
+        sequence: None,
+        txinwitness: Vec::new(),
+    }
+}
+
+fn script_sig_vin(hex: &str) -> Vin {
+    Vin {
+        coinbase: None,
+        txid: Some("11".repeat(32)),
+        vout: Some(0),
+        script_sig: Some(ScriptSig {
+            asm: None,
+            hex: Some(hex.to_string()),
+        }),
+        sequence: None,
+        txinwitness: Vec::new(),
+    }
+}
+
+fn vout(script_type: &str, hex: &str) -> Vout {
+    Vout {
+        value: Some(0.0),
+        n: None,
+        script_pub_key: Some(ScriptPubKey {
+            asm: None,
+            hex: Some(hex.to_string()),
+            script_type: Some(script_type.to_string()),
+        }),
+    }
+}
+
+fn finding(location: MessageLocation, index: usize, raw_hex: &str, message: &str) -> Finding {
+    Finding {
+        tx_hash: TX_HASH.to_string(),
+        location,
+        vin_or_vout_index: index,
+        item_index: None,
+        raw_hex: raw_hex.to_string(),
+        decoder: None,
+        message: message.to_string(),
+        entropy: shannon_entropy(message),
+        english_score: english_score(message),
+        pool: None,
+        timestamp: None,
+        file_type: None,
+        payload_length: None,
+        data_outputs: None,
+        significance: None,
+        protocol: None,
+        printable_ratio: None,
+        context: None,
+        signature: None,
+        kind: None,
+        kind_match: None,
+        hexdump: None,
+        address: None,
+        inscription_span: None,
+        source_file: None,
+        payload: None,
+    }
+}
+
+fn runs() -> ScanOptions {
+    ScanOptions {
+        mode: ExtractMode::Runs,
+        ..ScanOptions::default()
+    }
+}
+
+/// Bytes from a fixed xorshift generator, without any run of four printable
+/// ASCII characters.
+const RANDOM_HEX: &str = "9e3779b97f4a7c15f39cc0605cedc834\
+                          1082276bf3a27251f86c6a11d0c18e95\
+                          2767f0b153d27b7f0347045b5bf1827f";
+
+#[test]
+fn coinbase_tag_after_bip34_height() {
+    let coinbase = format!("03a0bb0d{}", hex::encode("/Foundry USA Pool/"));
+    let tx = transaction(vec![coinbase_vin(&coinbase)], Vec::new());
+
+    let mut expected = finding(
+        MessageLocation::CoinbaseTag,
+        0,
+        &coinbase,
+        "/Foundry USA Pool/",
+    );
+    expected.pool = Some("Foundry USA".to_string());
+    assert_eq!(check_transaction_for_messages(&tx), vec![expected]);
+}
+
+#[test]
+fn coinbase_run_with_timestamp() {
+    // Height 900000, a 1700000000 timestamp push, an extranonce push, then
+    // the miner tag.
+    let coinbase = format!(
+        "03a0bb0d0400f15365080000000000000000{}",
+        hex::encode("/Foundry USA Pool/")
+    );
+    let tx = transaction(vec![coinbase_vin(&coinbase)], Vec::new());
+
+    let mut expected = finding(
+        MessageLocation::Coinbase,
+        0,
+        &coinbase,
+        "/Foundry USA Pool/",
+    );
+    expected.pool = Some("Foundry USA".to_string());
+    expected.timestamp = Some("2023-11-14T22:13:20Z".to_string());
+    expected.printable_ratio = Some(0.5);
+    assert_eq!(check_transaction_with_options(&tx, &runs()), vec![expected]);
+}
+
+#[test]
+fn op_return_payload_without_opcodes() {
+    let script = format!("6a0b{}", hex::encode("hello world"));
+    let tx = transaction(Vec::new(), vec![vout("nulldata", &script)]);
+
+    assert_eq!(
+        check_transaction_for_messages(&tx),
+        vec![finding(
+            MessageLocation::OpReturn,
+            0,
+            &script,
+            "hello world"
+        )]
+    );
+}
+
+#[test]
+fn op_return_url_kind() {
+    let text = "see https://example.com/x.";
+    let script = format!("6a{:02x}{}", text.len(), hex::encode(text));
+    let tx = transaction(Vec::new(), vec![vout("nulldata", &script)]);
+
+    let mut expected = finding(MessageLocation::OpReturn, 0, &script, text);
+    expected.kind = Some(MessageKind::Url);
+    expected.kind_match = Some("https://example.com/x".to_string());
+    assert_eq!(check_transaction_for_messages(&tx), vec![expected]);
+
+    let options = ScanOptions {
+        filter: FindingFilter {
+            kind: Some(MessageKind::Email),
+            ..FindingFilter::default()
+        },
+        ..ScanOptions::default()
+    };
+    assert!(check_transaction_with_options(&tx, &options).is_empty());
+}
+
+#[test]
+fn only_selected_locations() {
+    let op_return = format!("6a0b{}", hex::encode("hello world"));
+    let script_pub_key = hex::encode("plain script");
+    let tx = transaction(
+        vec![script_sig_vin(&hex::encode("signature text"))],
+        vec![
+            vout("nulldata", &op_return),
+            vout("pubkeyhash", &script_pub_key),
+        ],
+    );
+    assert_eq!(check_transaction_for_messages(&tx).len(), 3);
+
+    let options = ScanOptions {
+        locations: Some(vec![MessageLocation::OpReturn]),
+        ..ScanOptions::default()
+    };
+    assert_eq!(
+        check_transaction_with_options(&tx, &options),
+        vec![finding(
+            MessageLocation::OpReturn,
+            0,
+            &op_return,
+            "hello world"
+        )]
+    );
+}
+
+#[test]
+fn op_return_utf8_emoji() {
+    let text = "gm ☕🚀";
+    let script = format!("6a{:02x}{}", text.len(), hex::encode(text));
+    let tx = transaction(Vec::new(), vec![vout("nulldata", &script)]);
+
+    assert!(check_transaction_for_messages(&tx).is_empty());
+    let options = ScanOptions {
+        encoding: Encoding::Utf8,
+        ..ScanOptions::default()
+    };
+    assert_eq!(
+        check_transaction_with_options(&tx, &options),
+        vec![finding(MessageLocation::OpReturn, 0, &script, text)]
+    );
+}
+
+#[test]
+fn embedded_files_have_no_message() {
+    let png = "89504e470d0a1a0a0000000d49484452";
+    let script = format!("6a10{}", png);
+    let tx = transaction(Vec::new(), vec![vout("nulldata", &script)]);
+
+    let mut expected = finding(MessageLocation::OpReturn, 0, &script, "");
+    expected.file_type = Some("png".to_string());
+    expected.payload_length = Some(16);
+    expected.payload = Some(hex::decode(png).unwrap());
+    assert_eq!(check_transaction_for_messages(&tx), vec![expected.clone()]);
+    assert_eq!(expected.to_string(), "OP_RETURN: [png file, 16 bytes]");
+
+    // Text filters have no text to look at.
+    let options = ScanOptions {
+        filter: FindingFilter {
+            search: Some("png".to_string()),
+            ..FindingFilter::default()
+        },
+        ..ScanOptions::default()
+    };
+    assert!(check_transaction_with_options(&tx, &options).is_empty());
+}
+
+#[test]
+fn op_return_protocols_are_labelled_instead_of_read() {
+    for (prefix, protocol) in [("434e545250525459", "Counterparty"), ("6f6d6e69", "Omni")] {
+        let payload = format!("{}{}", prefix, hex::encode("with some text"));
+        let script = format!("6a{:02x}{}", payload.len() / 2, payload);
+        let tx = transaction(Vec::new(), vec![vout("nulldata", &script)]);
+
+        let mut expected = finding(MessageLocation::OpReturn, 0, &script, "");
+        expected.protocol = Some(protocol.to_string());
+        expected.payload_length = Some(payload.len() / 2);
+        expected.payload = Some(hex::decode(&payload).unwrap());
+        assert_eq!(check_transaction_for_messages(&tx), vec![expected]);
+    }
+
+    // Without a known prefix, the payload is scanned as text.
+    let text = "counterparty data";
+    let script = format!("6a{:02x}{}", text.len(), hex::encode(text));
+    let tx = transaction(Vec::new(), vec![vout("nulldata", &script)]);
+    assert_eq!(identify_protocol(text.as_bytes()), None);
+    assert_eq!(
+        check_transaction_for_messages(&tx),
+        vec![finding(MessageLocation::OpReturn, 0, &script, text)]
+    );
+}
+
+#[test]
+fn bare_multisig_keys_carry_text() {
+    let text = "Text hidden in fake public keys of a bare multisig output";
+    let padded = format!("{:<62}", text);
+    let key = |prefix: &str, part: &str| format!("21{}{}00", prefix, hex::encode(part));
+    let script = format!(
+        "51{}{}52ae",
+        key("02", &padded[..31]),
+        key("03", &padded[31..])
+    );
+    let tx = transaction(Vec::new(), vec![vout("multisig", &script)]);
+
+    let p2ms: Vec<Finding> = check_transaction_for_messages(&tx)
+        .into_iter()
+        .filter(|finding| finding.location == MessageLocation::P2msData)
+        .collect();
+    assert_eq!(p2ms.len(), 1);
+    assert_eq!(p2ms[0].vin_or_vout_index, 0);
+    assert_eq!(p2ms[0].raw_hex, script);
+    assert_eq!(p2ms[0].message.trim_end(), text);
+
+    // Uncompressed keys hold 64 bytes of data each, with no trailing byte.
+    let padded = format!("{:<128}", text);
+    let key = |part: &str| format!("4104{}", hex::encode(part));
+    let script = format!("51{}{}52ae", key(&padded[..64]), key(&padded[64..]));
+    let tx = transaction(Vec::new(), vec![vout("multisig", &script)]);
+    let p2ms: Vec<Finding> = check_transaction_for_messages(&tx)
+        .into_iter()
+        .filter(|finding| finding.location == MessageLocation::P2msData)
+        .collect();
+    assert_eq!(p2ms.len(), 1);
+    assert_eq!(p2ms[0].message, padded);
+}
+
+#[test]
+fn split_op_returns_are_reassembled_in_output_order() {
+    let pieces = ["A message spread ", "over three ", "OP_RETURN outputs"];
+    let op_return = |piece: &str| {
+        let script = format!("6a{:02x}{}", piece.len(), hex::encode(piece));
+        vout("nulldata", &script)
+    };
+    let mut vouts = vec![vout(
+        "pubkeyhash",
+        &format!("76a914{}88ac", "ab".repeat(20)),
+    )];
+    vouts.extend(pieces.iter().map(|piece| op_return(piece)));
+    let tx = transaction(Vec::new(), vouts);
+
+    let reassembled = |options: &ScanOptions| -> Vec<Finding> {
+        check_transaction_with_options(&tx, options)
+            .into_iter()
+            .filter(|finding| finding.decoder.as_deref() == Some("reassembled"))
+            .collect()
+    };
+    let options = ScanOptions {
+        reassemble: true,
+        ..ScanOptions::default()
+    };
+    let findings = reassembled(&options);
+    assert_eq!(findings.len(), 1);
+    assert_eq!(findings[0].location, MessageLocation::OpReturn);
+    assert_eq!(findings[0].vin_or_vout_index, 1);
+    assert_eq!(findings[0].message, pieces.concat());
+    assert_eq!(findings[0].raw_hex, hex::encode(pieces.concat()));
+
+    assert!(reassembled(&ScanOptions::default()).is_empty());
+}
+
+#[test]
+fn compressed_op_returns_are_inflated() {
+    let text = "Compressed messages are inflated before they are scanned";
+    let gzip = {
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::best());
+        encoder.write_all(text.as_bytes()).unwrap();
+        encoder.finish().unwrap()
+    };
+    let zlib = {
+        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::best());
+        encoder.write_all(text.as_bytes()).unwrap();
+        encoder.finish().unwrap()
+    };
+
+    for (file_type, payload) in [("gzip", gzip), ("zlib", zlib)] {
+        let push = match payload.len() {
+            len @ 0..=75 => format!("{:02x}", len),
+            len => format!("4c{:02x}", len),
+        };
+        let script = format!("6a{}{}", push, hex::encode(&payload));
+        let tx = transaction(Vec::new(), vec![vout("nulldata", &script)]);
+
+        let findings = check_transaction_for_messages(&tx);
+        assert_eq!(findings.len(), 2, "{}", file_type);
+        assert_eq!(findings[0].file_type.as_deref(), Some(file_type));
+        assert_eq!(findings[0].payload_length, Some(payload.len()));
+        assert_eq!(findings[1].decoder.as_deref(), Some(file_type));
+        assert_eq!(findings[1].message, text);
+    }
+}
+
+#[test]
+fn text_is_not_mistaken_for_zlib() {
+    assert_eq!(detect_file_type(b"x\x9cnot deflate data"), None);
+    for text in ["x marks the spot", "x^2 + y^2 = r^2"] {
+        let script = format!("6a{:02x}{}", text.len(), hex::encode(text));
+        let tx = transaction(Vec::new(), vec![vout("nulldata", &script)]);
+        assert_eq!(detect_file_type(text.as_bytes()), None, "{}", text);
+        assert_eq!(
+            check_transaction_for_messages(&tx),
+            vec![finding(MessageLocation::OpReturn, 0, &script, text)]
+        );
+    }
+}
+
+#[test]
+fn search_ignores_case_beyond_ascii() {
+    let filter = FindingFilter {
+        search: Some("émile".to_string()),
+        ..FindingFilter::default()
+    };
+    let message = "Lettre à ÉMILE";
+    assert!(filter.matches(&finding(MessageLocation::OpReturn, 0, "", message)));
+    assert_eq!(filter.match_ranges(message), vec![10..16]);
+    assert_eq!(&message[10..16], "ÉMILE");
+
+    // 'İ' lowercases to two characters, which must not shift the ranges.
+    let filter = FindingFilter {
+        search: Some("STAN".to_string()),
+        ..FindingFilter::default()
+    };
+    let message = "İstanbul, İSTANBUL";
+    assert!(filter.matches(&finding(MessageLocation::OpReturn, 0, "", message)));
+    let ranges = filter.match_ranges(message);
+    let matched: Vec<&str> = ranges.iter().map(|range| &message[range.clone()]).collect();
+    assert_eq!(matched, ["stan", "STAN"]);
+}
+
+#[test]
+fn script_sig_with_binary_data() {
+    // A signature-sized push of random bytes followed by a short text push.
+    let script_sig = format!("30{}0d{}", RANDOM_HEX, hex::encode("can't be evil"));
+    let tx = transaction(vec![script_sig_vin(&script_sig)], Vec::new());
+
+    assert!(check_transaction_for_messages(&tx).is_empty());
+    let mut expected = finding(MessageLocation::ScriptSig, 0, &script_sig, "can't be evil");
+    expected.printable_ratio = Some(13.0 / 63.0);
+    assert_eq!(check_transaction_with_options(&tx, &runs()), vec![expected]);
+
+    let options = ScanOptions {
+        min_printable_ratio: Some(0.3),
+        ..runs()
+    };
+    assert!(check_transaction_with_options(&tx, &options).is_empty());
+}
+
+#[test]
+fn random_bytes_yield_nothing() {
+    let script = format!("6a30{}", RANDOM_HEX);
+    let tx = transaction(
+        vec![script_sig_vin(RANDOM_HEX)],
+        vec![vout("nulldata", &script), vout("nonstandard", RANDOM_HEX)],
+    );
+
+    assert!(check_transaction_for_messages(&tx).is_empty());
+    assert!(check_transaction_with_options(&tx, &runs()).is_empty());
+    assert_eq!(extract_hidden_message(RANDOM_HEX), None);
+}
+
+#[test]
+fn entropy_of_text_and_noise() {
+    assert_eq!(shannon_entropy(""), 0.0);
+    assert_eq!(shannon_entropy("aaaa"), 0.0);
+    assert_eq!(shannon_entropy("abab"), 1.0);
+    assert_eq!(shannon_entropy("abcd"), 2.0);
+
+    let text = shannon_entropy("The Times 03/Jan/2009 Chancellor on brink of second bailout");
+    let noise = shannon_entropy("k#9Qz!vR2@xL7&pW4^mB8*nT1%");
+    assert!(text < noise, "{} >= {}", text, noise);
+}
+
+#[test]
+fn recursive_hex_peels_nested_layers() {
+    let inner = hex::encode("hi there");
+    let outer = hex::encode(&inner);
+    let script = format!("6a{:02x}{}", outer.len(), hex::encode(&outer));
+    let tx = transaction(Vec::new(), vec![vout("nulldata", &script)]);
+
+    let options = ScanOptions {
+        recursive_hex: true,
+        ..ScanOptions::default()
+    };
+    let mut middle = finding(MessageLocation::OpReturn, 0, &script, &inner);
+    middle.decoder = Some("hex".to_string());
+    let mut text = finding(MessageLocation::OpReturn, 0, &script, "hi there");
+    text.decoder = Some("hex".to_string());
+    assert_eq!(
+        check_transaction_with_options(&tx, &options),
+        vec![
+            finding(MessageLocation::OpReturn, 0, &script, &outer),
+            middle,
+            text
+        ]
+    );
+}
+
+#[test]
+fn context_around_run() {
+    let script = format!("6a0b{}", hex::encode("hello world"));
+    let tx = transaction(Vec::new(), vec![vout("nulldata", &script)]);
+
+    let options = ScanOptions {
+        context: Some(4),
+        ..ScanOptions::default()
+    };
+    let findings = check_transaction_with_options(&tx, &options);
+    assert_eq!(
+        findings[0].context,
+        Some(Context {
+            offset: 2,
+            before: "6a0b".to_string(),
+            after: String::new(),
+        })
+    );
+    assert!(findings[0]
+        .to_string()
+        .ends_with("hello world [offset 2, before 6a0b, after -]"));
+}
+
+#[test]
+fn scan_bytes_returns_run_ranges() {
+    let data = b"\x00abcd\x01ab\x02wxyz";
+    assert_eq!(scan_bytes(data, 4), vec![1..5, 9..13]);
+    assert_eq!(scan_bytes(data, 2), vec![1..5, 6..8, 9..13]);
+    assert!(scan_bytes(&[0xff; 8], 1).is_empty());
+}
+
+/// Text stored back to front.
+struct Reversed;
+
+impl Decoder for Reversed {
+    fn name(&self) -> &str {
+        "reversed"
+    }
+
+    fn try_decode(&self, bytes: &[u8]) -> Option<String> {
+        let text = std::str::from_utf8(bytes).ok()?;
+        text.starts_with('!').then(|| text.chars().rev().collect())
+    }
+}
+
+#[test]
+fn registered_decoders_tag_their_findings() {
+    let xored: Vec<u8> = b"attack at dawn".iter().map(|b| b ^ 0x80).collect();
+    let reversed = "!dlrow olleh";
+    let xor_script = format!("6a{:02x}{}", xored.len(), hex::encode(&xored));
+    let rev_script = format!("6a{:02x}{}", reversed.len(), hex::encode(reversed));
+    let tx = transaction(
+        Vec::new(),
+        vec![vout("nulldata", &xor_script), vout("nulldata", &rev_script)],
+    );
+
+    let mut decoders = DecoderRegistry::new();
+    decoders.register(XorKey::new(vec![0x80]).unwrap());
+    decoders.register(Reversed);
+    let options = ScanOptions {
+        decoders,
+        ..ScanOptions::default()
+    };
+
+    let mut xor = finding(MessageLocation::OpReturn, 0, &xor_script, "attack at dawn");
+    xor.decoder = Some("xor 80".to_string());
+    let plain = finding(MessageLocation::OpReturn, 1, &rev_script, reversed);
+    let mut rev = finding(MessageLocation::OpReturn, 1, &rev_script, "hello world!");
+    rev.decoder = Some("reversed".to_string());
+    assert_eq!(
+        check_transaction_with_options(&tx, &options),
+        vec![xor, plain, rev]
+    );
+}
+
+#[test]
+fn utf16le_decoder() {
+    let bytes: Vec<u8> = "wide text"
+        .encode_utf16()
+        .flat_map(u16::to_le_bytes)
+        .collect();
+    assert_eq!(Utf16Le.try_decode(&bytes), Some("wide text".to_string()));
+    assert_eq!(Utf16Le.try_decode(&bytes[1..]), None);
+    assert_eq!(Utf16Le.try_decode(b"\x00\x00"), None);
+}
+
+#[test]
+fn xor_brute_force_finds_the_key() {
+    let text = "this is the message in the box";
+    let xored: Vec<u8> = text.bytes().map(|b| b ^ 0x9c).collect();
+    assert_eq!(xor_brute_force(&xored), Some((0x9c, text.to_string())));
+    // Printable payloads are left alone, as are ones without English.
+    assert_eq!(xor_brute_force(text.as_bytes()), None);
+    assert_eq!(xor_brute_force(&hex::decode(RANDOM_HEX).unwrap()), None);
+
+    let script = format!("6a{:02x}{}", xored.len(), hex::encode(&xored));
+    let tx = transaction(Vec::new(), vec![vout("nulldata", &script)]);
+    let options = ScanOptions {
+        xor_brute: true,
+        ..ScanOptions::default()
+    };
+    let mut expected = finding(MessageLocation::OpReturn, 0, &script, text);
+    expected.decoder = Some("xor 9c".to_string());
+    assert_eq!(
+        check_transaction_with_options(&tx, &options),
+        vec![expected]
+    );
+}
+
+#[test]
+fn addresses_of_standard_scripts() {
+    let p2pkh = hex::decode("76a91462e907b15cbf27d5425399ebf6f0fb50ebb88f1888ac").unwrap();
+    assert_eq!(
+        script_address(&p2pkh, Network::Mainnet).as_deref(),
+        Some("1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNa")
+    );
+    assert_eq!(
+        script_address(&p2pkh, Network::Testnet).as_deref(),
+        Some("mpXwg4jMtRhuSpVq4xS3HFHmCmWp9NyGKt")
+    );
+    let p2tr = format!("5120{}", "ab".repeat(32));
+    let p2tr = script_address(&hex::decode(p2tr).unwrap(), Network::Mainnet).unwrap();
+    assert!(p2tr.starts_with("bc1p"));
+    assert_eq!(
+        script_address(&hex::decode("6a0468656c6c6f").unwrap(), Network::Mainnet),
+        None
+    );
+}
+
+#[test]
+fn addresses_are_attached_to_output_findings() {
+    // A pay-to-pubkey-hash output whose "hash" is text.
+    let script = format!("76a914{}88ac", hex::encode("Hello from the chain"));
+    let tx = transaction(Vec::new(), vec![vout("pubkeyhash", &script)]);
+    let mut options = ScanOptions {
+        mode: ExtractMode::Runs,
+        ..ScanOptions::default()
+    };
+    let findings = check_transaction_with_options(&tx, &options);
+    assert_eq!(findings.len(), 1);
+    assert_eq!(findings[0].address, None);
+
+    options.addresses = true;
+    let findings = check_transaction_with_options(&tx, &options);
+    let address = findings[0].address.as_deref().unwrap();
+    assert!(address.starts_with('1'));
+    assert!(findings[0]
+        .annotations()
+        .contains(&format!("[address {}]", address)));
+}
+
+#[test]
+fn vanity_addresses_are_reported() {
+    let script = "76a91462e907b15cbf27d5425399ebf6f0fb50ebb88f1888ac";
+    let tx = transaction(
+        Vec::new(),
+        vec![
+            vout("nulldata", "6a0468656c6c6f"),
+            vout("pubkeyhash", script),
+        ],
+    );
+    let mut options = ScanOptions {
+        vanity: Some("P1eP5QGefi".to_string()),
+        locations: Some(vec![MessageLocation::Address]),
+        ..ScanOptions::default()
+    };
+    let findings = check_transaction_with_options(&tx, &options);
+    assert_eq!(findings.len(), 1);
+    assert_eq!(findings[0].location, MessageLocation::Address);
+    assert_eq!(findings[0].vin_or_vout_index, 1);
+    assert_eq!(findings[0].message, "1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNa");
+
+    options.vanity = Some("Love".to_string());
+    assert!(check_transaction_with_options(&tx, &options).is_empty());
+}
+
+#[test]
+fn only_the_first_input_of_a_block_is_the_coinbase() {
+    // BIP34 height 700000 and a miner tag.
+    let coinbase = format!("0360ae0a{}", hex::encode("/Mined by a test pool/"));
+    // A signature and a compressed public key, then a note.
+    let script_sig = format!(
+        "47{}012102{}16{}",
+        "8f".repeat(70),
+        "c1".repeat(32),
+        hex::encode("hello from the spender")
+    );
+    let options = ScanOptions {
+        mode: ExtractMode::Runs,
+        ..ScanOptions::default()
+    };
+    let locations = |findings: Vec<Finding>| -> Vec<(MessageLocation, String)> {
+        findings
+            .into_iter()
+            .map(|finding| (finding.location, finding.message))
+            .collect()
+    };
+
+    let coinbase_tx = transaction(vec![coinbase_vin(&coinbase)], Vec::new());
+    let spend = transaction(vec![script_sig_vin(&script_sig)], Vec::new());
+    let mined_by = "/Mined by a test pool/".to_string();
+    let note = "hello from the spender".to_string();
+    assert_eq!(
+        locations(check_block_transaction(&coinbase_tx, 0, &options)),
+        vec![(MessageLocation::Coinbase, mined_by.clone())]
+    );
+    assert_eq!(
+        locations(check_block_transaction(&spend, 1, &options)),
+        vec![(MessageLocation::ScriptSig, note.clone())]
+    );
+
+    // The position decides, not which field the source filled in.
+    let unmarked_coinbase = transaction(vec![script_sig_vin(&coinbase)], Vec::new());
+    assert_eq!(
+        locations(check_block_transaction(&unmarked_coinbase, 0, &options)),
+        vec![(MessageLocation::Coinbase, mined_by)]
+    );
+    let marked_spend = transaction(vec![coinbase_vin(&script_sig)], Vec::new());
+    assert_eq!(
+        locations(check_block_transaction(&marked_spend, 1, &options)),
+        vec![(MessageLocation::ScriptSig, note)]
+    );
+}
+
+#[test]
+fn many_identical_data_outputs_look_like_artwork() {
+    let outputs = |values: &dyn Fn(usize) -> f64| -> Vec<Vout> {
+        (0..25)
+            .map(|i| {
+                let hash = format!("{:02x}", 0x80 + i).repeat(20);
+                let mut vout = vout("pubkeyhash", &format!("76a914{}88ac", hash));
+                vout.value = Some(values(i));
+                vout
+            })
+            .collect()
+    };
+    let artwork = |tx: &Transaction| -> Vec<Finding> {
+        check_transaction_for_messages(tx)
+            .into_iter()
+            .filter(|finding| finding.kind == Some(MessageKind::Artwork))
+            .collect()
+    };
+
+    let mut vouts = outputs(&|_| 0.00000001);
+    vouts.insert(
+        0,
+        vout("witness_v0_keyhash", &format!("0014{}", "ab".repeat(20))),
+    );
+    let findings = artwork(&transaction(Vec::new(), vouts));
+    assert_eq!(findings.len(), 1);
+    assert_eq!(findings[0].location, MessageLocation::ScriptPubKey);
+    assert_eq!(findings[0].vin_or_vout_index, 1);
+    assert!(!findings[0].has_message());
+    assert_eq!(findings[0].data_outputs, Some(25));
+    assert_eq!(
+        findings[0].to_string(),
+        "ScriptPubKey: [25 data outputs] [artwork]"
+    );
+
+    // Payouts of different amounts are an ordinary batch.
+    let batch = transaction(Vec::new(), outputs(&|i| i as f64));
+    assert!(artwork(&batch).is_empty());
+}
+
+#[test]
+fn op_return_hashes_are_labelled_as_references() {
+    let hash: Vec<u8> = (0..32u8).map(|i| i.wrapping_mul(7) ^ 0x80).collect();
+    let script = format!("6a20{}", hex::encode(&hash));
+    let tx = transaction(Vec::new(), vec![vout("nulldata", &script)]);
+
+    let mut expected = finding(MessageLocation::OpReturn, 0, &script, &hex::encode(&hash));
+    expected.kind = Some(MessageKind::HashReference);
+    expected.kind_match = Some(hex::encode(&hash));
+    let findings = check_transaction_for_messages(&tx);
+    assert_eq!(findings, vec![expected]);
+    assert_eq!(findings[0].annotations(), " [hash_reference]");
+}
@@ -0,0 +1,55 @@
+use base64::prelude::*;
+use bitcoin::hashes::Hash;
+use bitcoin::secp256k1::{Message, Secp256k1, SecretKey};
+use bitcoin::sign_message::{signed_msg_hash, MessageSignature};
+use bitcoin::{Address, Network, PublicKey};
+use bitcoin_message_finder::{parse_signed_message, SignatureCheck};
+
+/// Signs `message` with a fixed key, returning the P2PKH address and the
+/// base64 signature.
+fn sign(message: &str) -> (String, String) {
+    let secp = Secp256k1::new();
+    let secret_key = SecretKey::from_slice(&[0x42; 32]).unwrap();
+    let public_key = PublicKey::new(secret_key.public_key(&secp));
+    let digest = Message::from_digest(signed_msg_hash(message).to_byte_array());
+    let signature = secp.sign_ecdsa_recoverable(&digest, &secret_key);
+    let signature = MessageSignature::new(signature, true);
+    (
+        Address::p2pkh(public_key, Network::Bitcoin).to_string(),
+        BASE64_STANDARD.encode(signature.serialize()),
+    )
+}
+
+#[test]
+fn inline_signed_message() {
+    let (address, signature) = sign("I own this");
+    let text = format!("{} {} I own this", address, signature);
+
+    let signed = parse_signed_message(&text).unwrap();
+    assert_eq!(signed.message, "I own this");
+    assert_eq!(
+        signed.check(),
+        SignatureCheck {
+            address: address.clone(),
+            valid: true
+        }
+    );
+
+    let forged = format!("{} {} I own that", signature, address);
+    assert!(!parse_signed_message(&forged).unwrap().check().valid);
+}
+
+#[test]
+fn armored_signed_message() {
+    let (address, signature) = sign("hello\nworld");
+    let text = format!(
+        "-----BEGIN BITCOIN SIGNED MESSAGE-----\nhello\nworld\n-----BEGIN SIGNATURE-----\n{}\n{}\n-----END BITCOIN SIGNED MESSAGE-----",
+        address, signature
+    );
+    assert!(parse_signed_message(&text).unwrap().check().valid);
+}
+
+#[test]
+fn plain_text_is_not_signed() {
+    assert_eq!(parse_signed_message("hello world again"), None);
+}
@@ -0,0 +1,305 @@
+use std::time::{Duration, Instant};
+
+use bitcoin_message_finder::{
+    build_client, fetch_block_stream, get_with_retry, parse_json_response, search_block_time,
+    Backend, BlockId, BlockInfo, BlockSource, BlockVisitor, BlockchainInfoSource, Cache,
+    ClientOptions, FetchError, Network, Tip, Transaction,
+};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+#[test]
+fn json_response() {
+    let tip: Tip = parse_json_response(
+        200,
+        Some("application/json; charset=utf-8"),
+        r#"{"height": 1, "hash": "00ab"}"#,
+    )
+    .unwrap();
+    assert_eq!(tip.height, 1);
+    assert_eq!(tip.hash, "00ab");
+}
+
+#[test]
+fn html_error_page_is_not_json() {
+    let body = "<html><body>Too Many Requests</body></html>";
+    let error = parse_json_response::<Tip>(429, Some("text/html"), body).unwrap_err();
+    match &error {
+        FetchError::NonJson { status, snippet } => {
+            assert_eq!(*status, 429);
+            assert_eq!(snippet, body);
+        }
+        other => panic!("unexpected error: {:?}", other),
+    }
+    assert!(error.to_string().contains("you may be rate limited"));
+}
+
+#[test]
+fn long_bodies_are_truncated() {
+    let body = "x".repeat(1000);
+    match parse_json_response::<Tip>(502, None, &body) {
+        Err(FetchError::NonJson { snippet, .. }) => assert!(snippet.len() < body.len()),
+        other => panic!("unexpected result: {:?}", other.map(|tip| tip.height)),
+    }
+}
+
+#[tokio::test]
+async fn unresponsive_server_times_out() {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}/", listener.local_addr().unwrap());
+    // Accept connections but never answer them.
+    tokio::spawn(async move {
+        let mut open = Vec::new();
+        while let Ok((socket, _)) = listener.accept().await {
+            open.push(socket);
+        }
+    });
+
+    let client = build_client(&ClientOptions {
+        timeout: Duration::from_millis(50),
+        ..ClientOptions::default()
+    })
+    .unwrap();
+    // Clients keep their own settings.
+    build_client(&ClientOptions::default()).unwrap();
+    let error = get_with_retry(&client, &url).await.unwrap_err();
+    assert!(
+        matches!(&error, FetchError::Timeout { after, .. } if *after == Duration::from_millis(50)),
+        "unexpected error: {:?}",
+        error
+    );
+    assert!(error.to_string().contains("timed out after 0.05s"));
+}
+
+#[tokio::test]
+async fn rate_limits_hold_per_client() {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}/", listener.local_addr().unwrap());
+    tokio::spawn(async move {
+        while let Ok((mut socket, _)) = listener.accept().await {
+            tokio::spawn(async move {
+                let mut request = [0; 1024];
+                while socket.read(&mut request).await.is_ok_and(|n| n > 0) {
+                    let response = "HTTP/1.1 200 OK\r\nContent-Length: 2\r\n\r\nok";
+                    let _ = socket.write_all(response.as_bytes()).await;
+                }
+            });
+        }
+    });
+
+    let client = build_client(&ClientOptions {
+        max_requests_per_sec: Some(10.0),
+        ..ClientOptions::default()
+    })
+    .unwrap();
+    // An unlimited client built later leaves the first one limited.
+    let unlimited = build_client(&ClientOptions::default()).unwrap();
+    get_with_retry(&unlimited, &url).await.unwrap();
+
+    let start = Instant::now();
+    for _ in 0..12 {
+        get_with_retry(&client, &url).await.unwrap();
+    }
+    assert!(start.elapsed() >= Duration::from_millis(150));
+}
+
+/// Serves a response with a `len` byte body to every connection.
+async fn serve(len: usize) -> String {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}/block", listener.local_addr().unwrap());
+    tokio::spawn(async move {
+        while let Ok((mut socket, _)) = listener.accept().await {
+            let mut request = [0; 1024];
+            let _ = socket.read(&mut request).await;
+            let head = format!("HTTP/1.1 200 OK\r\nContent-Length: {}\r\n\r\n", len);
+            let _ = socket.write_all(head.as_bytes()).await;
+            let _ = socket.write_all(&vec![b'x'; len]).await;
+        }
+    });
+    url
+}
+
+#[tokio::test]
+async fn bodies_over_the_limit_are_refused() {
+    let client = build_client(&ClientOptions {
+        max_block_bytes: Some(100),
+        ..ClientOptions::default()
+    })
+    .unwrap();
+
+    let small = serve(100).await;
+    assert_eq!(get_with_retry(&client, &small).await.unwrap().len(), 100);
+
+    let large = serve(101).await;
+    let error = get_with_retry(&client, &large).await.unwrap_err();
+    assert!(
+        matches!(&error, FetchError::TooLarge { limit: 100, .. }),
+        "unexpected error: {:?}",
+        error
+    );
+}
+
+/// Answers every connection with the head of its request.
+async fn echo_request() -> String {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}/", listener.local_addr().unwrap());
+    tokio::spawn(async move {
+        while let Ok((mut socket, _)) = listener.accept().await {
+            let mut request = [0; 1024];
+            let len = socket.read(&mut request).await.unwrap_or(0);
+            let head = format!("HTTP/1.1 200 OK\r\nContent-Length: {}\r\n\r\n", len);
+            let _ = socket.write_all(head.as_bytes()).await;
+            let _ = socket.write_all(&request[..len]).await;
+        }
+    });
+    url
+}
+
+#[tokio::test]
+async fn api_key_is_only_sent_with_api_requests() {
+    let client = build_client(&ClientOptions {
+        api_key: Some("secret".to_string()),
+        ..ClientOptions::default()
+    })
+    .unwrap();
+    let url = echo_request().await;
+
+    let api_request = get_with_retry(&client, &url).await.unwrap();
+    let api_request = String::from_utf8_lossy(&api_request).to_lowercase();
+    assert!(api_request.contains("x-api-key: secret"), "{}", api_request);
+    let other = client.inner().post(&url).send().await.unwrap();
+    let other = other.text().await.unwrap().to_lowercase();
+    assert!(!other.contains("x-api-key"), "{}", other);
+}
+
+#[tokio::test]
+async fn cached_blocks_over_the_limit_are_refused() {
+    let dir = std::env::temp_dir().join(format!("bmf-cache-{}", std::process::id()));
+    let cache = Cache::new(dir.clone(), false);
+    let block = format!(
+        r#"{{"height": 1, "tx": [], "padding": "{}"}}"#,
+        "x".repeat(200)
+    );
+    let hash = "ab".repeat(32);
+    cache.put(
+        &format!("https://blockchain.info/rawblock/{}?format=json", hash),
+        &block,
+    );
+    let client = build_client(&ClientOptions {
+        max_block_bytes: Some(100),
+        ..ClientOptions::default()
+    })
+    .unwrap();
+    let source = BlockchainInfoSource::new(client, Network::Mainnet).with_cache(Some(cache));
+
+    let fetched = source.fetch(BlockId::Hash(hash.clone())).await;
+    assert!(matches!(
+        fetched,
+        Err(FetchError::TooLarge { limit: 100, .. })
+    ));
+    let streamed = source.fetch_stream(BlockId::Hash(hash)).await;
+    assert!(matches!(
+        streamed,
+        Err(FetchError::TooLarge { limit: 100, .. })
+    ));
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+/// Serves `body` as JSON to every connection.
+async fn serve_json(body: &'static str) -> String {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}/block", listener.local_addr().unwrap());
+    tokio::spawn(async move {
+        while let Ok((mut socket, _)) = listener.accept().await {
+            let mut request = [0; 1024];
+            let _ = socket.read(&mut request).await;
+            let head = "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\n\r\n";
+            let _ = socket.write_all(head.as_bytes()).await;
+            let _ = socket.write_all(body.as_bytes()).await;
+        }
+    });
+    url
+}
+
+#[derive(Default)]
+struct Hashes(Vec<String>);
+
+impl BlockVisitor for Hashes {
+    fn start(&mut self, _: &BlockInfo) {}
+
+    fn transaction(&mut self, tx: Transaction) -> bool {
+        self.0.push(tx.hash);
+        true
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn streamed_blocks_are_cached_once_parsed() {
+    let dir = std::env::temp_dir().join(format!("bmf-stream-{}", std::process::id()));
+    let cache = Cache::new(dir.clone(), false);
+    let client = build_client(&ClientOptions::default()).unwrap();
+
+    let url =
+        serve_json(r#"{"blocks": [{"height": 1, "tx": [{"hash": "a1"}, {"hash": "b2"}]}]}"#).await;
+    let stream = fetch_block_stream(&client, Some(&cache), &url, "1".to_string())
+        .await
+        .unwrap();
+    let mut hashes = Hashes::default();
+    stream.visit(&mut hashes).unwrap();
+    assert_eq!(hashes.0, ["a1", "b2"]);
+    assert!(cache.get(&url).is_some());
+
+    let truncated = serve_json(r#"{"blocks": [{"height": 2, "tx": [{"hash": "c3"}"#).await;
+    let stream = fetch_block_stream(&client, Some(&cache), &truncated, "2".to_string())
+        .await
+        .unwrap();
+    assert!(stream.visit(&mut Hashes::default()).is_err());
+    assert!(cache.get(&truncated).is_none());
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn blocks_are_only_cached_by_hash() {
+    let dir = std::env::temp_dir().join(format!("bmf-reorg-{}", std::process::id()));
+    let cache = Cache::new(dir.clone(), false);
+    let by_height = "https://mempool.space/api/block-height/900000";
+    let by_hash = format!("https://mempool.space/api/block/{}", "cd".repeat(32));
+    cache.put(by_height, "a hash that a reorg can replace");
+    cache.put(&by_hash, "{}");
+
+    assert!(cache.get(by_height).is_none());
+    assert!(cache.open(by_height).is_none());
+    assert!(cache.writer(by_height).is_none());
+    assert_eq!(cache.get(&by_hash).as_deref(), Some("{}"));
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn requests_per_block_follow_the_backend() {
+    assert_eq!(Backend::BlockchainInfo.requests_per_block(false, 3000), 1);
+    assert_eq!(Backend::Mempool.requests_per_block(true, 3000), 2);
+    assert_eq!(Backend::Mempool.requests_per_block(false, 1), 3);
+    assert_eq!(Backend::Mempool.requests_per_block(false, 50), 4);
+    assert_eq!(Backend::Mempool.requests_per_block(false, 51), 5);
+}
+
+#[tokio::test]
+async fn block_times_are_binary_searched() {
+    // One block every 600 seconds, with block 3 mined before block 2.
+    let times = [1000, 1600, 2200, 2100, 3400, 4000];
+    let tip = times.len() as u64 - 1;
+    let mut lookups = 0;
+    let mut search = async |timestamp| {
+        search_block_time(tip, timestamp, async |height| {
+            lookups += 1;
+            Ok(times[height as usize])
+        })
+        .await
+        .unwrap()
+    };
+    assert_eq!(search(0).await, Some(0));
+    assert_eq!(search(1600).await, Some(1));
+    assert_eq!(search(3000).await, Some(4));
+    assert_eq!(search(4000).await, Some(5));
+    assert_eq!(search(4001).await, None);
+    assert!(lookups <= 5 * 3);
+}
@@ -0,0 +1,68 @@
+use bitcoin_message_finder::{
+    check_transaction_with_options, detect_steganography, strip_invisible, zero_width_bits,
+    Encoding, MessageKind, ScanOptions, ScriptPubKey, Transaction, Vout,
+};
+
+/// `text` hidden as zero-width spaces and non-joiners after `cover`.
+fn hide(cover: &str, text: &str) -> String {
+    let mut hidden = cover.to_string();
+    for byte in text.bytes() {
+        for bit in (0..8).rev() {
+            hidden.push(if byte >> bit & 1 == 1 {
+                '\u{200c}'
+            } else {
+                '\u{200b}'
+            });
+        }
+    }
+    hidden
+}
+
+#[test]
+fn zero_width_bit_stream() {
+    let text = hide("hello", "hi!");
+    assert_eq!(zero_width_bits(&text), Some("hi!".to_string()));
+    assert_eq!(strip_invisible(&text), "hello");
+    assert_eq!(detect_steganography(&text), Some("hi!".to_string()));
+}
+
+#[test]
+fn other_invisible_characters() {
+    let text = "gm\u{200d}\u{feff} frens\u{200d}";
+    assert_eq!(zero_width_bits(text), None);
+    assert_eq!(
+        detect_steganography(text),
+        Some("3 invisible characters (U+200D, U+FEFF)".to_string())
+    );
+    assert_eq!(detect_steganography("gm frens"), None);
+}
+
+#[test]
+fn utf8_findings_are_tagged() {
+    let text = hide("nothing to see", "key");
+    let script = format!("6a4c{:02x}{}", text.len(), hex::encode(&text));
+    let tx = Transaction {
+        hash: "00ff".to_string(),
+        hex: None,
+        vin: Vec::new(),
+        vout: vec![Vout {
+            value: Some(0.0),
+            n: None,
+            script_pub_key: Some(ScriptPubKey {
+                asm: None,
+                hex: Some(script),
+                script_type: Some("nulldata".to_string()),
+            }),
+        }],
+    };
+    let options = ScanOptions {
+        encoding: Encoding::Utf8,
+        ..ScanOptions::default()
+    };
+
+    let findings = check_transaction_with_options(&tx, &options);
+    assert_eq!(findings.len(), 1);
+    assert_eq!(findings[0].message, text);
+    assert_eq!(findings[0].kind, Some(MessageKind::Steganography));
+    assert_eq!(findings[0].kind_match.as_deref(), Some("key"));
+}
@@ -0,0 +1,92 @@
+use bitcoin_message_finder::{BlockInfo, BlockStream, BlockVisitor, FetchError, Transaction};
+
+#[derive(Default)]
+struct Collect {
+    info: Option<BlockInfo>,
+    hashes: Vec<String>,
+    limit: Option<usize>,
+}
+
+impl BlockVisitor for Collect {
+    fn start(&mut self, info: &BlockInfo) {
+        self.info = Some(*info);
+    }
+
+    fn transaction(&mut self, tx: Transaction) -> bool {
+        self.hashes.push(tx.hash);
+        self.limit.is_none_or(|limit| self.hashes.len() < limit)
+    }
+}
+
+fn json(body: &str) -> BlockStream {
+    BlockStream::Json {
+        label: "test".to_string(),
+        body: body.to_string(),
+    }
+}
+
+const THREE: &str = r#"{"hash": "ff", "n_tx": 3, "height": 7, "tx": [
+    {"hash": "a1", "vin": [], "vout": []},
+    {"hash": "b2", "vin": [], "vout": []},
+    {"hash": "c3", "vin": [], "vout": []}
+], "weight": 1}"#;
+
+#[test]
+fn transactions_are_visited_in_order() {
+    let mut collect = Collect::default();
+    json(THREE).visit(&mut collect).unwrap();
+    assert_eq!(
+        collect.info,
+        Some(BlockInfo {
+            height: Some(7),
+            tx_count: Some(3),
+        })
+    );
+    assert_eq!(collect.hashes, ["a1", "b2", "c3"]);
+}
+
+#[test]
+fn visitors_can_stop_early() {
+    let mut collect = Collect {
+        limit: Some(1),
+        ..Collect::default()
+    };
+    json(THREE).visit(&mut collect).unwrap();
+    assert_eq!(collect.hashes, ["a1"]);
+}
+
+#[test]
+fn only_the_first_listed_block_is_visited() {
+    let body = format!(r#"{{"blocks": [{}, {{"tx": [{{"hash": "d4"}}]}}]}}"#, THREE);
+    let mut collect = Collect::default();
+    json(&body).visit(&mut collect).unwrap();
+    assert_eq!(collect.hashes, ["a1", "b2", "c3"]);
+}
+
+#[test]
+fn readers_are_parsed_incrementally() {
+    let mut collect = Collect::default();
+    BlockStream::Reader {
+        label: "test".to_string(),
+        reader: Box::new(THREE.as_bytes()),
+    }
+    .visit(&mut collect)
+    .unwrap();
+    assert_eq!(collect.hashes.len(), 3);
+}
+
+#[test]
+fn empty_block_lists_have_no_blocks() {
+    let err = json(r#"{"blocks": []}"#)
+        .visit(&mut Collect::default())
+        .unwrap_err();
+    assert!(matches!(err, FetchError::NoBlocks(label) if label == "test"));
+}
+
+#[test]
+fn blocks_need_transactions() {
+    let err = json(r#"{"height": 7}"#)
+        .visit(&mut Collect::default())
+        .unwrap_err();
+    assert!(err.to_string().contains("missing field `tx`"), "{}", err);
+}